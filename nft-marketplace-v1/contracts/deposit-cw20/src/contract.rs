@@ -1,103 +1,655 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order,
-    Response, StdError, StdResult, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::Cw20ReceiveMsg;
-use cw721::Cw721ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::{Approval, Cw721ReceiveMsg};
+use cw_storage_plus::Bound;
+use cw_utils::{Expiration, Scheduled};
 
 use crate::error::ContractError;
 use crate::msg::{
-    Cw20DepositResponse, Cw20HookMsg, Cw721DepositResponse, Cw721HookMsg, DepositResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    AskStatus, AsksResponse, BidsResponse, CollectionOffersResponse, CreditProceedsResponse,
+    CurrentPriceResponse, Cw20DepositResponse, Cw20HookMsg, Cw2981QueryMsg, Cw721DepositResponse,
+    Cw721HookMsg, DepositDenomsResponse, DepositResponse, ExecuteMsg, InstantiateMsg,
+    OperatorsResponse, QueryMsg, RoyaltiesInfoResponse,
 };
 use crate::state::{
-    Cw20Deposit, Cw721Deposit, Deposit, Offer, ASKS, CW20_DEPOSITS, CW721_DEPOSITS, DEPOSITS,
+    bids, Auction, Bid, BidKey, BidTerms, Bundle, BundleItem, CollectionOffer, CollectionRoyalty,
+    Config, Currency, Cw20Deposit, Cw721Deposit, Deposit, Offer, PriceDecline, SealedAuction,
+    SealedBid, Swap, TokenRef, TopUp, TraitCriterion, ASKS, AUCTIONS, BUNDLES, BUNDLE_COUNT,
+    BUNDLE_ITEMS, COLLECTION_OFFERS, COLLECTION_OFFER_COUNT, COLLECTION_ROYALTIES, CONFIG,
+    CREDIT_PROCEEDS, CW20_DEPOSITS, CW721_DEPOSITS, DEPOSITS, DEPOSIT_DENOMS, OPERATORS,
+    SEALED_AUCTIONS, SEALED_BIDS, SWAPS, SWAP_COUNT, SWAP_ITEMS,
 };
 
-use nft;
+use nft::helpers::NftContract;
+use sha2::{Digest, Sha256};
 
 const CONTRACT_NAME: &str = "deposit-cw20-example";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::new().add_attribute("method", "instantiate"))
+
+    if msg.fee_bps > 10_000 {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?.to_string(),
+        None => info.sender.to_string(),
+    };
+    let fee_recipient = match msg.fee_recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?.to_string(),
+        None => admin.clone(),
+    };
+    let config = Config {
+        admin,
+        fee_bps: msg.fee_bps,
+        fee_recipient,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("admin", config.admin)
+        .add_attribute("fee_bps", config.fee_bps.to_string())
+        .add_attribute("fee_recipient", config.fee_recipient))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Receive(cw20_msg) => receive_cw20(deps, info, cw20_msg),
-        ExecuteMsg::WithdrawCw20 { owner, amount } => try_withdraw_cw20(deps, info, owner, amount),
+        ExecuteMsg::UpdateConfig {
+            admin,
+            fee_bps,
+            fee_recipient,
+        } => execute_update_config(deps, info, admin, fee_bps, fee_recipient),
+        ExecuteMsg::SetCollectionRoyalty {
+            cw721_contract,
+            payment_address,
+            share_bps,
+        } => execute_set_collection_royalty(deps, info, cw721_contract, payment_address, share_bps),
+        ExecuteMsg::UpdateOperators { add, remove } => {
+            execute_update_operators(deps, info, add, remove)
+        }
+        ExecuteMsg::UpdateDepositDenoms { add, remove } => {
+            execute_update_deposit_denoms(deps, info, add, remove)
+        }
+        ExecuteMsg::Receive(cw20_msg) => receive_cw20(deps, env, info, cw20_msg),
+        ExecuteMsg::PurchaseFromBalance {
+            cw721_contract,
+            token_id,
+        } => execute_purchase_from_balance(deps, env, info, cw721_contract, token_id),
+        ExecuteMsg::SetCreditProceeds { enabled } => {
+            execute_set_credit_proceeds(deps, info, enabled)
+        }
+        ExecuteMsg::WithdrawCw20 {
+            cw20_contract,
+            amount,
+            recipient,
+        } => try_withdraw_cw20(deps, info, cw20_contract, amount, recipient),
         ExecuteMsg::Deposit {} => try_deposit(deps, info),
         ExecuteMsg::Withdraw { amount, denom } => try_withdraw_deposit(deps, info, amount, denom),
-        ExecuteMsg::ReceiveNft(cw721_msg) => receive_cw721(deps, info, cw721_msg),
+        ExecuteMsg::ReceiveNft(cw721_msg) => receive_cw721(deps, env, info, cw721_msg),
         ExecuteMsg::WithdrawNft {
             cw721_contract,
             token_id,
         } => try_withdraw_cw721(deps, info, cw721_contract, token_id),
+        ExecuteMsg::Purchase {
+            cw721_contract,
+            token_id,
+        } => execute_native_purchase(deps, env, info, cw721_contract, token_id),
+        ExecuteMsg::PurchaseWithAllowance {
+            cw721_contract,
+            token_id,
+            cw20_contract,
+        } => execute_purchase_with_allowance(
+            deps,
+            env,
+            info,
+            cw721_contract,
+            token_id,
+            cw20_contract,
+        ),
+        ExecuteMsg::UpdateAsk {
+            cw721_contract,
+            token_id,
+            price,
+            currency,
+            expires_at,
+        } => execute_update_ask(
+            deps,
+            info,
+            cw721_contract,
+            token_id,
+            price,
+            currency,
+            expires_at,
+        ),
+        ExecuteMsg::CancelAsk {
+            cw721_contract,
+            token_id,
+        } => execute_cancel_ask(deps, info, cw721_contract, token_id),
+        ExecuteMsg::ListWithApproval {
+            cw721_contract,
+            token_id,
+            currency,
+            price,
+            expires_at,
+            starts_at,
+        } => {
+            let ask = Offer {
+                owner: info.sender.to_string(),
+                token_id,
+                cw721_contract,
+                currency,
+                amount: price,
+                decline: None,
+                expires_at,
+                starts_at,
+                custodial: false,
+            };
+            execute_list_with_approval(deps, env, ask)
+        }
+        ExecuteMsg::InvalidateAsk {
+            cw721_contract,
+            token_id,
+        } => execute_invalidate_ask(deps, env, cw721_contract, token_id),
+        ExecuteMsg::Bid {
+            cw721_contract,
+            token_id,
+            expires_at,
+        } => execute_native_bid(deps, info, cw721_contract, token_id, expires_at),
+        ExecuteMsg::RetractBid {
+            cw721_contract,
+            token_id,
+        } => execute_retract_bid(deps, info, cw721_contract, token_id),
+        ExecuteMsg::AcceptBid {
+            cw721_contract,
+            token_id,
+            bidder,
+            terms,
+        } => execute_accept_listed_bid(deps, env, info, cw721_contract, token_id, bidder, terms),
+        ExecuteMsg::CreateCollectionOffer {
+            cw721_contract,
+            price,
+            quantity,
+            traits,
+        } => execute_native_collection_offer(deps, info, cw721_contract, price, quantity, traits),
+        ExecuteMsg::CancelCollectionOffer {
+            cw721_contract,
+            offer_id,
+        } => execute_cancel_collection_offer(deps, info, cw721_contract, offer_id),
+        ExecuteMsg::BidAuction {
+            cw721_contract,
+            token_id,
+        } => execute_native_auction_bid(deps, env, info, cw721_contract, token_id),
+        ExecuteMsg::SettleAuction {
+            cw721_contract,
+            token_id,
+        } => execute_settle_auction(deps, env, cw721_contract, token_id),
+        ExecuteMsg::SettleAuctions { start_after, limit } => {
+            execute_settle_auctions(deps, env, start_after, limit)
+        }
+        ExecuteMsg::CommitSealedBid {
+            cw721_contract,
+            token_id,
+            commitment,
+        } => {
+            execute_native_commit_sealed_bid(deps, env, info, cw721_contract, token_id, commitment)
+        }
+        ExecuteMsg::RevealSealedBid {
+            cw721_contract,
+            token_id,
+            amount,
+            salt,
+        } => execute_reveal_sealed_bid(deps, env, info, cw721_contract, token_id, amount, salt),
+        ExecuteMsg::PruneExpired {
+            asks_start_after,
+            bids_start_after,
+            limit,
+        } => execute_prune_expired(deps, env, asks_start_after, bids_start_after, limit),
+        ExecuteMsg::CreateBundle { currency, price } => {
+            execute_create_bundle(deps, info, currency, price)
+        }
+        ExecuteMsg::ListBundle { bundle_id } => execute_list_bundle(deps, info, bundle_id),
+        ExecuteMsg::CancelBundle { bundle_id } => execute_cancel_bundle(deps, info, bundle_id),
+        ExecuteMsg::PurchaseMany { items, best_effort } => {
+            execute_native_purchase_many(deps, env, info, items, best_effort.unwrap_or_default())
+        }
+        ExecuteMsg::PurchaseBundle { bundle_id } => {
+            execute_native_purchase_bundle(deps, info, bundle_id)
+        }
+        ExecuteMsg::CreateSwap {
+            counterparty,
+            wanted,
+            top_up,
+        } => execute_create_swap(deps, info, counterparty, wanted, top_up),
+        ExecuteMsg::OpenSwap { swap_id } => execute_open_swap(deps, info, swap_id),
+        ExecuteMsg::FillSwap { swap_id } => execute_native_fill_swap(deps, info, swap_id),
+        ExecuteMsg::RetractSwapFill { swap_id } => execute_retract_swap_fill(deps, info, swap_id),
+        ExecuteMsg::CancelSwap { swap_id } => execute_cancel_swap(deps, info, swap_id),
+        ExecuteMsg::SettleSealedAuction {
+            cw721_contract,
+            token_id,
+            limit,
+        } => execute_settle_sealed_auction(deps, env, cw721_contract, token_id, limit),
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: Option<String>,
+    fee_bps: Option<u64>,
+    fee_recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(admin) = admin {
+        config.admin = deps.api.addr_validate(&admin)?.to_string();
+    }
+    if let Some(fee_bps) = fee_bps {
+        if fee_bps > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        config.fee_bps = fee_bps;
+    }
+    if let Some(fee_recipient) = fee_recipient {
+        config.fee_recipient = deps.api.addr_validate(&fee_recipient)?.to_string();
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "update_config")
+        .add_attribute("admin", config.admin)
+        .add_attribute("fee_bps", config.fee_bps.to_string())
+        .add_attribute("fee_recipient", config.fee_recipient))
+}
+
+pub fn execute_update_operators(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for operator in &add {
+        let operator = deps.api.addr_validate(operator)?;
+        OPERATORS.save(deps.storage, operator.as_str(), &Empty {})?;
+    }
+    for operator in &remove {
+        OPERATORS.remove(deps.storage, operator);
+    }
+
+    Ok(Response::new()
+        .add_attribute("execute", "update_operators")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+pub fn execute_update_deposit_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<Coin>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for coin in &add {
+        DEPOSIT_DENOMS.save(deps.storage, &coin.denom, &coin.amount)?;
+    }
+    for denom in &remove {
+        DEPOSIT_DENOMS.remove(deps.storage, denom);
+    }
+
+    Ok(Response::new()
+        .add_attribute("execute", "update_deposit_denoms")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+pub fn execute_set_collection_royalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    payment_address: String,
+    share_bps: u64,
+) -> Result<Response, ContractError> {
+    let minter = NftContract(deps.api.addr_validate(&cw721_contract)?).minter(&deps.querier)?;
+    if info.sender != minter.minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if share_bps > 10_000 {
+        return Err(ContractError::InvalidRoyalty {});
     }
+
+    let royalty = CollectionRoyalty {
+        payment_address: deps.api.addr_validate(&payment_address)?.to_string(),
+        share_bps,
+    };
+    COLLECTION_ROYALTIES.save(deps.storage, &cw721_contract, &royalty)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "set_collection_royalty")
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("payment_address", royalty.payment_address)
+        .add_attribute("share_bps", royalty.share_bps.to_string()))
 }
 
 pub fn receive_cw20(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::Deposit { owner, amount }) => {
-            execute_cw20_deposit(deps, info, owner, amount)
+        Ok(Cw20HookMsg::Deposit {}) => {
+            execute_cw20_deposit(deps, info, cw20_msg.sender, cw20_msg.amount.u128())
+        }
+        Ok(Cw20HookMsg::DepositFor { owner }) => {
+            check_operator(deps.as_ref(), &cw20_msg.sender)?;
+            let owner = deps.api.addr_validate(&owner)?.to_string();
+            execute_cw20_deposit(deps, info, owner, cw20_msg.amount.u128())
         }
         Ok(Cw20HookMsg::Purchase {
             token_id,
             cw721_contract,
-        }) => execute_purchase(deps, info, token_id, cw721_contract, cw20_msg),
-        Err(_) => todo!(),
+        }) => execute_purchase(deps, env, info, token_id, cw721_contract, cw20_msg),
+        Ok(Cw20HookMsg::Bid {
+            cw721_contract,
+            token_id,
+            expires_at,
+        }) => {
+            let bid = Bid {
+                bidder: cw20_msg.sender,
+                cw721_contract,
+                token_id,
+                currency: Currency::Cw20 {
+                    contract: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount.u128(),
+                expires_at,
+            };
+            execute_place_bid(deps, bid)
+        }
+        Ok(Cw20HookMsg::CreateCollectionOffer {
+            cw721_contract,
+            price,
+            quantity,
+            traits,
+        }) => {
+            let offer = CollectionOffer {
+                id: 0,
+                buyer: cw20_msg.sender,
+                cw721_contract,
+                currency: Currency::Cw20 {
+                    contract: info.sender.to_string(),
+                },
+                price,
+                quantity,
+                traits: traits.unwrap_or_default(),
+            };
+            execute_create_collection_offer(deps, offer, cw20_msg.amount)
+        }
+        Ok(Cw20HookMsg::BidAuction {
+            cw721_contract,
+            token_id,
+        }) => {
+            let bid = Bid {
+                bidder: cw20_msg.sender,
+                cw721_contract,
+                token_id,
+                currency: Currency::Cw20 {
+                    contract: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount.u128(),
+                expires_at: None,
+            };
+            execute_auction_bid(deps, env, bid)
+        }
+        Ok(Cw20HookMsg::CommitSealedBid {
+            cw721_contract,
+            token_id,
+            commitment,
+        }) => {
+            let currency = Currency::Cw20 {
+                contract: info.sender.to_string(),
+            };
+            let bid = SealedBid {
+                bidder: cw20_msg.sender,
+                commitment,
+                collateral: cw20_msg.amount.u128(),
+                revealed: None,
+            };
+            execute_commit_sealed_bid(deps, env, cw721_contract, token_id, currency, bid)
+        }
+        Ok(Cw20HookMsg::PurchaseBundle { bundle_id }) => {
+            execute_purchase_bundle(deps, info, bundle_id, cw20_msg)
+        }
+        Ok(Cw20HookMsg::PurchaseMany { items, best_effort }) => {
+            let currency = Currency::Cw20 {
+                contract: info.sender.to_string(),
+            };
+            execute_purchase_many(
+                deps,
+                env,
+                cw20_msg.sender,
+                currency,
+                cw20_msg.amount,
+                items,
+                best_effort.unwrap_or_default(),
+            )
+        }
+        Ok(Cw20HookMsg::FillSwap { swap_id }) => {
+            let currency = Currency::Cw20 {
+                contract: info.sender.to_string(),
+            };
+            execute_fill_swap_top_up(deps, cw20_msg.sender, currency, cw20_msg.amount, swap_id)
+        }
+        Err(_) => Err(ContractError::UnknownHookMsg {}),
     }
 }
 
 pub fn receive_cw721(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw721_msg: Cw721ReceiveMsg,
 ) -> Result<Response, ContractError> {
     match from_binary(&cw721_msg.msg) {
         Ok(Cw721HookMsg::Deposit {
+            currency,
+            amount,
+            expires_at,
+            starts_at,
+        }) => {
+            let ask = Offer {
+                owner: cw721_msg.sender,
+                token_id: cw721_msg.token_id,
+                cw721_contract: info.sender.to_string(),
+                currency,
+                amount,
+                decline: None,
+                expires_at,
+                starts_at,
+                custodial: true,
+            };
+            execute_cw721_deposit(deps, ask)
+        }
+        Ok(Cw721HookMsg::DepositFor {
             owner,
-            token_id,
-            cw20_contract,
+            currency,
             amount,
-        }) => execute_cw721_deposit(deps, info, owner, token_id, cw20_contract, amount),
-        Err(_) => todo!(),
+            expires_at,
+            starts_at,
+        }) => {
+            check_operator(deps.as_ref(), &cw721_msg.sender)?;
+            let ask = Offer {
+                owner: deps.api.addr_validate(&owner)?.to_string(),
+                token_id: cw721_msg.token_id,
+                cw721_contract: info.sender.to_string(),
+                currency,
+                amount,
+                decline: None,
+                expires_at,
+                starts_at,
+                custodial: true,
+            };
+            execute_cw721_deposit(deps, ask)
+        }
+        Ok(Cw721HookMsg::AcceptBid { bidder, terms }) => execute_accept_bid(
+            deps,
+            env,
+            cw721_msg.sender,
+            info.sender.to_string(),
+            cw721_msg.token_id,
+            bidder,
+            terms,
+        ),
+        Ok(Cw721HookMsg::AddToBundle { bundle_id }) => execute_add_to_bundle(
+            deps,
+            cw721_msg.sender,
+            info.sender.to_string(),
+            cw721_msg.token_id,
+            bundle_id,
+        ),
+        Ok(Cw721HookMsg::OfferToSwap { swap_id }) => execute_offer_to_swap(
+            deps,
+            cw721_msg.sender,
+            info.sender.to_string(),
+            cw721_msg.token_id,
+            swap_id,
+        ),
+        Ok(Cw721HookMsg::FillSwap { swap_id }) => execute_fill_swap_nft(
+            deps,
+            cw721_msg.sender,
+            info.sender.to_string(),
+            cw721_msg.token_id,
+            swap_id,
+        ),
+        Ok(Cw721HookMsg::FillCollectionOffer { offer_id }) => execute_fill_collection_offer(
+            deps,
+            cw721_msg.sender,
+            info.sender.to_string(),
+            cw721_msg.token_id,
+            offer_id,
+        ),
+        Ok(Cw721HookMsg::CreateAuction {
+            currency,
+            reserve,
+            min_increment,
+            end_time,
+            extension_window,
+        }) => {
+            let auction = Auction {
+                seller: cw721_msg.sender,
+                cw721_contract: info.sender.to_string(),
+                token_id: cw721_msg.token_id,
+                currency,
+                reserve,
+                min_increment,
+                end_time,
+                extension_window: extension_window.unwrap_or_default(),
+                highest_bidder: None,
+                highest_bid: 0,
+            };
+            execute_create_auction(deps, env, auction)
+        }
+        Ok(Cw721HookMsg::CreateDutchAuction {
+            currency,
+            start_price,
+            end_price,
+            start_time,
+            end_time,
+            step_interval,
+        }) => {
+            if start_price < end_price || end_time <= start_time || step_interval == Some(0) {
+                return Err(ContractError::InvalidPriceDecline {});
+            }
+
+            let ask = Offer {
+                owner: cw721_msg.sender,
+                token_id: cw721_msg.token_id,
+                cw721_contract: info.sender.to_string(),
+                currency,
+                amount: start_price,
+                decline: Some(PriceDecline {
+                    start_price,
+                    end_price,
+                    start_time,
+                    end_time,
+                    step_interval,
+                }),
+                expires_at: None,
+                starts_at: None,
+                custodial: true,
+            };
+            execute_cw721_deposit(deps, ask)
+        }
+        Ok(Cw721HookMsg::CreateSealedAuction {
+            currency,
+            reserve,
+            commit_end,
+            reveal_end,
+            non_reveal_penalty_bps,
+        }) => {
+            let auction = SealedAuction {
+                seller: cw721_msg.sender,
+                cw721_contract: info.sender.to_string(),
+                token_id: cw721_msg.token_id,
+                currency,
+                reserve,
+                commit_end,
+                reveal_end,
+                non_reveal_penalty_bps: non_reveal_penalty_bps.unwrap_or_default(),
+                highest_bidder: None,
+                highest_bid: 0,
+                settled: false,
+            };
+            execute_create_sealed_auction(deps, env, auction)
+        }
+        Err(_) => Err(ContractError::UnknownHookMsg {}),
     }
 }
 
-pub fn execute_cw721_deposit(
-    deps: DepsMut,
-    info: MessageInfo,
-    owner: String,
-    token_id: String,
-    cw20_contract: String,
-    amount: u128,
-) -> Result<Response, ContractError> {
-    let contract_addr = info.sender.clone().to_string();
+pub fn execute_cw721_deposit(deps: DepsMut, ask: Offer) -> Result<Response, ContractError> {
+    let owner = ask.owner.clone();
+    let contract_addr = ask.cw721_contract.clone();
+    let token_id = ask.token_id.clone();
 
     match CW721_DEPOSITS.load(deps.storage, (&owner, &contract_addr, &token_id)) {
-        Ok(_) => return Err(ContractError::Cw721AlreadyDeposited {}),
+        Ok(_) => Err(ContractError::Cw721AlreadyDeposited {}),
         Err(_) => {
             let deposit = Cw721Deposit {
                 owner: owner.clone(),
@@ -107,23 +659,15 @@ pub fn execute_cw721_deposit(
 
             CW721_DEPOSITS.save(deps.storage, (&owner, &contract_addr, &token_id), &deposit)?;
 
-            let ask = Offer {
-                owner: owner.clone(),
-                token_id: token_id.clone(),
-                cw721_contract: contract_addr.clone().to_string(),
-                cw20_contract: cw20_contract.clone(),
-                amount,
-            };
-
-            ASKS.save(deps.storage, (&contract_addr.to_string(), &token_id), &ask)?;
+            ASKS.save(deps.storage, (&contract_addr, &token_id), &ask)?;
 
             Ok(Response::new()
                 .add_attribute("execute", "deposit_cw721")
                 .add_attribute("owner", owner)
                 .add_attribute("cw721_contract", contract_addr)
                 .add_attribute("token_id", token_id)
-                .add_attribute("cw20_addr", cw20_contract)
-                .add_attribute("amount_requested", amount.to_string()))
+                .add_attribute("currency", ask.currency.to_string())
+                .add_attribute("amount_requested", ask.amount.to_string()))
         }
     }
 }
@@ -138,15 +682,21 @@ pub fn execute_cw20_deposit(
 
     match CW20_DEPOSITS.load(deps.storage, (&owner, &contract_addr)) {
         Ok(mut deposit) => {
-            deposit.amount = deposit.amount.checked_add(amount.clone()).unwrap();
-            deposit.count = deposit.count.checked_add(1).unwrap();
+            deposit.amount = deposit
+                .amount
+                .checked_add(amount)
+                .ok_or(ContractError::Overflow {})?;
+            deposit.count = deposit
+                .count
+                .checked_add(1)
+                .ok_or(ContractError::Overflow {})?;
 
             CW20_DEPOSITS.save(deps.storage, (&owner, &contract_addr), &deposit)?;
         }
         Err(_) => {
             let deposit = Cw20Deposit {
                 owner: owner.clone(),
-                amount: amount.clone(),
+                amount,
                 contract: contract_addr.clone(),
                 count: 1,
             };
@@ -162,56 +712,118 @@ pub fn execute_cw20_deposit(
         .add_attribute("contract", contract_addr))
 }
 
+//the depositor pulls from their own balance, identified by the sender rather than the message
 pub fn try_withdraw_cw20(
     deps: DepsMut,
     info: MessageInfo,
-    owner: String,
+    cw20_contract: String,
     amount: u128,
+    recipient: Option<String>,
 ) -> Result<Response, ContractError> {
-    let contract_addr = info.sender.clone().to_string();
+    //cw20 refuses a zero transfer, which would fail the whole tx with a less useful error
+    if amount == 0 {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let owner = info.sender.to_string();
+    let recipient = match recipient {
+        Some(addr) => deps.api.addr_validate(&addr)?.to_string(),
+        None => owner.clone(),
+    };
 
-    match CW20_DEPOSITS.load(deps.storage, (&owner, &contract_addr)) {
+    match CW20_DEPOSITS.load(deps.storage, (&owner, &cw20_contract)) {
         Ok(mut deposit) => {
-            deposit.count = deposit.count.checked_sub(1).unwrap();
-            deposit.amount = deposit.amount.checked_sub(amount.clone()).unwrap();
+            deposit.amount = match deposit.amount.checked_sub(amount) {
+                Some(remaining) => remaining,
+                None => return Err(ContractError::InsufficientBalance {}),
+            };
+            deposit.count = deposit.count.saturating_sub(1);
 
-            CW20_DEPOSITS.save(deps.storage, (&owner, &contract_addr), &deposit)?;
+            CW20_DEPOSITS.save(deps.storage, (&owner, &cw20_contract), &deposit)?;
+
+            let currency = Currency::Cw20 {
+                contract: cw20_contract.clone(),
+            };
+            let msg = payment_msg(&currency, Uint128::new(amount), &recipient)?;
 
             Ok(Response::new()
                 .add_attribute("execute", "withdraw_cw20")
                 .add_attribute("amount", amount.to_string())
-                .add_attribute("from", contract_addr)
-                .add_attribute("to", owner))
+                .add_attribute("from", owner)
+                .add_attribute("contract", cw20_contract)
+                .add_attribute("to", recipient)
+                .add_message(msg))
         }
         Err(_) => Err(ContractError::NoCw20ToWithdraw {}),
     }
 }
 
+//credits every coin sent, each denom to its own balance
 pub fn try_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-    let sender = info.sender.clone().to_string();
-    let funds = info.funds[0].clone();
+    let sender = info.sender.to_string();
 
-    match DEPOSITS.load(deps.storage, (&sender, &funds.denom)) {
-        Ok(mut deposit) => {
-            deposit.amount.amount = deposit.amount.amount.checked_add(funds.amount).unwrap();
-            deposit.count = deposit.count.checked_add(1).unwrap();
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
 
-            DEPOSITS.save(deps.storage, (&sender, &funds.denom), &deposit)?;
+    //check every coin before crediting any of them
+    let restricted = DEPOSIT_DENOMS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    for coin in &info.funds {
+        if coin.amount.is_zero() {
+            return Err(ContractError::ZeroAmount {});
         }
-        Err(_) => {
-            let deposit = Deposit {
-                owner: sender.clone(),
-                amount: funds.clone(),
-                count: 1,
-            };
+        if restricted {
+            match DEPOSIT_DENOMS.may_load(deps.storage, &coin.denom)? {
+                Some(minimum) if coin.amount < minimum => {
+                    return Err(ContractError::DepositBelowMinimum {
+                        denom: coin.denom.clone(),
+                        minimum,
+                    })
+                }
+                Some(_) => {}
+                None => {
+                    return Err(ContractError::DenomNotAllowed {
+                        denom: coin.denom.clone(),
+                    })
+                }
+            }
+        }
+    }
+
+    for coin in &info.funds {
+        match DEPOSITS.load(deps.storage, (&sender, &coin.denom)) {
+            Ok(mut deposit) => {
+                deposit.amount.amount = deposit
+                    .amount
+                    .amount
+                    .checked_add(coin.amount)
+                    .map_err(|_| ContractError::Overflow {})?;
+                deposit.count = deposit
+                    .count
+                    .checked_add(1)
+                    .ok_or(ContractError::Overflow {})?;
+
+                DEPOSITS.save(deps.storage, (&sender, &coin.denom), &deposit)?;
+            }
+            Err(_) => {
+                let deposit = Deposit {
+                    owner: sender.clone(),
+                    amount: coin.clone(),
+                    count: 1,
+                };
 
-            DEPOSITS.save(deps.storage, (&sender, &funds.denom), &deposit)?;
+                DEPOSITS.save(deps.storage, (&sender, &coin.denom), &deposit)?;
+            }
         }
     }
+
+    let funds: Vec<String> = info.funds.iter().map(|coin| coin.to_string()).collect();
     Ok(Response::new()
         .add_attribute("execute", "deposit")
-        .add_attribute("amount", funds.amount.to_string())
-        .add_attribute("denom", funds.denom))
+        .add_attribute("owner", sender)
+        .add_attribute("funds", funds.join(",")))
 }
 
 pub fn try_withdraw_deposit(
@@ -224,12 +836,12 @@ pub fn try_withdraw_deposit(
 
     match DEPOSITS.load(deps.storage, (&sender, &denom)) {
         Ok(mut deposit) => {
-            deposit.count = deposit.count.checked_sub(1).unwrap();
+            deposit.count = deposit.count.saturating_sub(1);
             deposit.amount.amount = deposit
                 .amount
                 .amount
                 .checked_sub(Uint128::from(amount))
-                .unwrap();
+                .map_err(|_| ContractError::InsufficientBalance {})?;
 
             let msg = BankMsg::Send {
                 to_address: sender.clone(),
@@ -247,7 +859,7 @@ pub fn try_withdraw_deposit(
                 .add_attribute("to", sender)
                 .add_message(msg))
         }
-        Err(_) => todo!(),
+        Err(_) => Err(ContractError::UnknownDenom { denom }),
     }
 }
 
@@ -259,13 +871,22 @@ pub fn try_withdraw_cw721(
 ) -> Result<Response, ContractError> {
     match CW721_DEPOSITS.load(
         deps.storage,
-        (&info.sender.clone().to_string(), &cw721_contract, &token_id),
+        (info.sender.as_ref(), &cw721_contract, &token_id),
     ) {
         Ok(_) => {
+            //bundled items only come back out with the whole bundle
+            if BUNDLE_ITEMS.has(deps.storage, (&cw721_contract, &token_id)) {
+                return Err(ContractError::ItemInBundle {});
+            }
+            if SWAP_ITEMS.has(deps.storage, (&cw721_contract, &token_id)) {
+                return Err(ContractError::ItemInSwap {});
+            }
+
             CW721_DEPOSITS.remove(
                 deps.storage,
-                (&info.sender.clone().to_string(), &cw721_contract, &token_id),
+                (info.sender.as_ref(), &cw721_contract, &token_id),
             );
+            ASKS.remove(deps.storage, (&cw721_contract, &token_id));
 
             let exec_msg = nft::contract::ExecuteMsg::TransferNft {
                 recipient: info.sender.clone().to_string(),
@@ -284,88 +905,2337 @@ pub fn try_withdraw_cw721(
                 .add_attribute("receiver_address", info.sender.to_string())
                 .add_message(msg))
         }
-        Err(_) => return Err(ContractError::NoCw721ToWithdraw {}),
+        Err(_) => Err(ContractError::NoCw721ToWithdraw {}),
     }
 }
 
-pub fn execute_purchase(
+pub fn execute_update_ask(
     deps: DepsMut,
-    _info: MessageInfo,
-    token_id: String,
+    info: MessageInfo,
     cw721_contract: String,
-    cw20_msg: Cw20ReceiveMsg,
+    token_id: String,
+    price: u128,
+    currency: Currency,
+    expires_at: Option<Expiration>,
 ) -> Result<Response, ContractError> {
-    //let buyer = info.sender.clone().to_string();
-
-    let original_owner: String;
+    let mut ask = match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => ask,
+        Err(_) => return Err(ContractError::NoAsk {}),
+    };
 
-    match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
-        Ok(ask) => {
-            if Uint128::new(ask.amount) != cw20_msg.amount {
-                return Err(ContractError::InvalidBid {});
-            }
-
-            original_owner = ask.owner.clone();
+    if info.sender != ask.owner {
+        return Err(ContractError::InvalidOwner {});
+    }
 
-            let exec_msg = nft::contract::ExecuteMsg::TransferNft {
-                recipient: cw20_msg.sender.clone(),
-                token_id: token_id.clone(),
-            };
-            let msg = WasmMsg::Execute {
-                contract_addr: cw721_contract.clone(),
-                msg: to_binary(&exec_msg)?,
-                funds: vec![],
-            };
-            CW721_DEPOSITS.remove(deps.storage, (&ask.owner, &cw721_contract, &token_id));
-            ASKS.remove(deps.storage, (&cw721_contract, &token_id));
+    ask.amount = price;
+    ask.currency = currency;
+    ask.decline = None;
+    ask.expires_at = expires_at;
+    ASKS.save(deps.storage, (&cw721_contract, &token_id), &ask)?;
 
-            Ok(Response::new()
-                .add_attribute("execute", "nft_purchase")
-                .add_attribute("token_id", token_id)
-                .add_attribute("from", original_owner)
-                .add_attribute("to", cw20_msg.sender)
-                .add_message(msg))
-        }
-        Err(_) => return Err(ContractError::NoBidsForTokenID {}),
-    }
+    Ok(Response::new()
+        .add_attribute("execute", "update_ask")
+        .add_attribute("owner", ask.owner)
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("currency", ask.currency.to_string())
+        .add_attribute("amount_requested", ask.amount.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetCw20Deposit { address } => to_binary(&try_query_cw20_deposit(deps, address)?),
-        QueryMsg::GetDeposits { address } => to_binary(&try_query_deposit(deps, address)?),
-        QueryMsg::GetCw721Deposit { address, contract } => {
-            to_binary(&try_query_cw721_deposit(deps, address, contract)?)
-        }
-    }
-}
+//a custodial ask and its deposit always go together
+pub fn execute_cancel_ask(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let ask = match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => ask,
+        Err(_) => return Err(ContractError::NoAsk {}),
+    };
 
-pub fn try_query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
-    let _valid_addr = deps.api.addr_validate(&address)?;
+    if info.sender != ask.owner {
+        return Err(ContractError::InvalidOwner {});
+    }
 
-    let res: StdResult<Vec<_>> = DEPOSITS
-        .prefix(&address)
-        .range(deps.storage, None, None, Order::Ascending)
-        .collect();
+    ASKS.remove(deps.storage, (&cw721_contract, &token_id));
 
-    let deposits_found = res?;
+    let mut res = Response::new()
+        .add_attribute("execute", "cancel_ask")
+        .add_attribute("owner", ask.owner.clone())
+        .add_attribute("cw721_contract", cw721_contract.clone())
+        .add_attribute("token_id", token_id.clone());
 
-    if deposits_found.len() == 0 {
-        return Err(StdError::generic_err("No deposits found for that address"));
+    //approval listings never left the seller's wallet
+    if ask.custodial {
+        CW721_DEPOSITS.remove(deps.storage, (&ask.owner, &cw721_contract, &token_id));
+        res = res.add_message(transfer_nft_msg(&cw721_contract, &token_id, &ask.owner)?);
     }
 
-    let mut deposits: Vec<Deposit> = vec![];
+    Ok(res)
+}
 
-    for deposit in deposits_found {
-        deposits.push(deposit.1);
+//replaces any earlier ask for the token, the seller is checked to be its current owner
+pub fn execute_list_with_approval(
+    deps: DepsMut,
+    env: Env,
+    ask: Offer,
+) -> Result<Response, ContractError> {
+    if !approval_listing_valid(deps.as_ref(), &env, &ask) {
+        return Err(ContractError::NotApproved {});
     }
 
-    Ok(DepositResponse { deposits })
-}
+    ASKS.save(deps.storage, (&ask.cw721_contract, &ask.token_id), &ask)?;
 
-pub fn try_query_cw20_deposit(deps: Deps, address: String) -> StdResult<Cw20DepositResponse> {
-    let _valid_addr = deps.api.addr_validate(&address)?;
+    Ok(Response::new()
+        .add_attribute("execute", "list_with_approval")
+        .add_attribute("owner", ask.owner)
+        .add_attribute("cw721_contract", ask.cw721_contract)
+        .add_attribute("token_id", ask.token_id)
+        .add_attribute("currency", ask.currency.to_string())
+        .add_attribute("amount_requested", ask.amount.to_string()))
+}
+
+pub fn execute_invalidate_ask(
+    deps: DepsMut,
+    env: Env,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let ask = match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => ask,
+        Err(_) => return Err(ContractError::NoAsk {}),
+    };
+
+    if ask.custodial || approval_listing_valid(deps.as_ref(), &env, &ask) {
+        return Err(ContractError::AskStillValid {});
+    }
+
+    ASKS.remove(deps.storage, (&cw721_contract, &token_id));
+
+    Ok(Response::new()
+        .add_attribute("execute", "invalidate_ask")
+        .add_attribute("owner", ask.owner)
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_purchase(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    cw721_contract: String,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => {
+            //the cw20 contract calling us is the token being paid with
+            match &ask.currency {
+                Currency::Cw20 { contract } if info.sender == *contract => {}
+                _ => return Err(ContractError::InvalidCoin {}),
+            }
+
+            settle_purchase(deps, &env, ask, cw20_msg.sender, cw20_msg.amount)
+        }
+        Err(_) => Err(ContractError::NoAsk {}),
+    }
+}
+
+pub fn execute_native_purchase(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => {
+            let denom = match &ask.currency {
+                Currency::Native { denom } => denom.clone(),
+                _ => return Err(ContractError::InvalidCoin {}),
+            };
+
+            if info.funds.len() != 1 || info.funds[0].denom != denom {
+                return Err(ContractError::InvalidCoin {});
+            }
+
+            let paid = info.funds[0].amount;
+            settle_purchase(deps, &env, ask, info.sender.to_string(), paid)
+        }
+        Err(_) => Err(ContractError::NoAsk {}),
+    }
+}
+
+//the buyer sets an allowance on the cw20 and the marketplace pulls exactly the current price
+pub fn execute_purchase_with_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+    cw20_contract: String,
+) -> Result<Response, ContractError> {
+    //the price comes out of the allowance, native coins sent along would just be kept
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => {
+            match &ask.currency {
+                Currency::Cw20 { contract } if cw20_contract == *contract => {}
+                _ => return Err(ContractError::InvalidCoin {}),
+            }
+
+            let price = current_price(&ask, &env);
+            let pull_msg: CosmosMsg = WasmMsg::Execute {
+                contract_addr: cw20_contract,
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: price,
+                })?,
+                funds: vec![],
+            }
+            .into();
+
+            let mut res = settle_purchase(deps, &env, ask, info.sender.to_string(), price)?;
+            //the funds have to arrive before the payouts go out
+            res.messages.insert(0, SubMsg::new(pull_msg));
+            Ok(res)
+        }
+        Err(_) => Err(ContractError::NoAsk {}),
+    }
+}
+
+pub fn execute_native_purchase_many(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    items: Vec<TokenRef>,
+    best_effort: bool,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let currency = Currency::Native {
+        denom: info.funds[0].denom.clone(),
+    };
+    let paid = info.funds[0].amount;
+    execute_purchase_many(
+        deps,
+        env,
+        info.sender.to_string(),
+        currency,
+        paid,
+        items,
+        best_effort,
+    )
+}
+
+//settles each ask at its current price out of one payment and refunds what's left
+pub fn execute_purchase_many(
+    mut deps: DepsMut,
+    env: Env,
+    buyer: String,
+    currency: Currency,
+    paid: Uint128,
+    items: Vec<TokenRef>,
+    best_effort: bool,
+) -> Result<Response, ContractError> {
+    let mut remaining = paid;
+    let mut msgs = vec![];
+    let mut bought = 0u64;
+
+    for item in items {
+        //only the checks can skip an item, once its sale starts writing any error fails the batch
+        let checked = match ASKS.load(deps.storage, (&item.cw721_contract, &item.token_id)) {
+            Ok(ask) if ask.currency != currency => Err(ContractError::InvalidCoin {}),
+            Ok(ask) => {
+                check_purchase(deps.as_ref(), &env, &ask, remaining).map(|price| (ask, price))
+            }
+            Err(_) => Err(ContractError::NoAsk {}),
+        };
+
+        match checked {
+            Ok((ask, price)) => {
+                let res = complete_purchase(deps.branch(), ask, buyer.clone(), price, price)?;
+                remaining -= price;
+                bought += 1;
+                msgs.extend(res.messages);
+            }
+            Err(err) if !best_effort => return Err(err),
+            Err(_) => {}
+        }
+    }
+
+    if bought == 0 {
+        return Err(ContractError::NothingPurchased {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("execute", "purchase_many")
+        .add_attribute("buyer", buyer.clone())
+        .add_attribute("currency", currency.to_string())
+        .add_attribute("bought", bought.to_string())
+        .add_attribute("amount", paid - remaining)
+        .add_submessages(msgs);
+
+    if !remaining.is_zero() {
+        res = res.add_message(payment_msg(&currency, remaining, &buyer)?);
+    }
+
+    Ok(res)
+}
+
+//the funds are already held by the marketplace, so only the ledger moves
+pub fn execute_purchase_from_balance(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    match ASKS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(ask) => {
+            let buyer = info.sender.to_string();
+            let currency = ask.currency.clone();
+            let price = current_price(&ask, &env);
+            if internal_balance(deps.as_ref(), &buyer, &currency)? < price {
+                return Err(ContractError::InsufficientBalance {});
+            }
+
+            let res = settle_purchase(deps.branch(), &env, ask, buyer.clone(), price)?;
+            debit_balance(deps.storage, &buyer, &currency, price)?;
+            Ok(res.add_attribute("paid_from", "balance"))
+        }
+        Err(_) => Err(ContractError::NoAsk {}),
+    }
+}
+
+pub fn execute_set_credit_proceeds(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    if enabled {
+        CREDIT_PROCEEDS.save(deps.storage, info.sender.as_str(), &Empty {})?;
+    } else {
+        CREDIT_PROCEEDS.remove(deps.storage, info.sender.as_str());
+    }
+
+    Ok(Response::new()
+        .add_attribute("execute", "set_credit_proceeds")
+        .add_attribute("seller", info.sender)
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+//checks the ask can be bought now for what was paid, then moves the nft and the funds
+fn settle_purchase(
+    deps: DepsMut,
+    env: &Env,
+    ask: Offer,
+    buyer: String,
+    paid: Uint128,
+) -> Result<Response, ContractError> {
+    let price = check_purchase(deps.as_ref(), env, &ask, paid)?;
+    complete_purchase(deps, ask, buyer, paid, price)
+}
+
+//every reason a purchase can be refused, checked before anything is written
+fn check_purchase(
+    deps: Deps,
+    env: &Env,
+    ask: &Offer,
+    paid: Uint128,
+) -> Result<Uint128, ContractError> {
+    if is_expired(&ask.expires_at, env) {
+        return Err(ContractError::Expired {});
+    }
+    if !is_started(&ask.starts_at, env) {
+        return Err(ContractError::AskNotStarted {});
+    }
+    if !ask.custodial && !approval_listing_valid(deps, env, ask) {
+        return Err(ContractError::StaleAsk {});
+    }
+
+    let price = current_price(ask, env);
+    if paid < price {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    Ok(price)
+}
+
+fn complete_purchase(
+    mut deps: DepsMut,
+    ask: Offer,
+    buyer: String,
+    paid: Uint128,
+    price: Uint128,
+) -> Result<Response, ContractError> {
+    let nft_msg = transfer_nft_msg(&ask.cw721_contract, &ask.token_id, &buyer)?;
+    let payout_msgs = sale_payout_msgs(
+        deps.branch(),
+        &ask.cw721_contract,
+        &ask.token_id,
+        &ask.currency,
+        price,
+        &ask.owner,
+    )?;
+
+    if ask.custodial {
+        CW721_DEPOSITS.remove(
+            deps.storage,
+            (&ask.owner, &ask.cw721_contract, &ask.token_id),
+        );
+    }
+    ASKS.remove(deps.storage, (&ask.cw721_contract, &ask.token_id));
+
+    let mut res = Response::new()
+        .add_attribute("execute", "nft_purchase")
+        .add_attribute("token_id", ask.token_id)
+        .add_attribute("from", ask.owner)
+        .add_attribute("to", buyer.clone())
+        .add_attribute("currency", ask.currency.to_string())
+        .add_attribute("amount", price)
+        .add_message(nft_msg)
+        .add_messages(payout_msgs);
+
+    //send back anything paid over the current price
+    if paid > price {
+        res = res.add_message(payment_msg(&ask.currency, paid - price, &buyer)?);
+    }
+
+    Ok(res)
+}
+
+//the bundle id is assigned here
+pub fn execute_create_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    currency: Currency,
+    price: u128,
+) -> Result<Response, ContractError> {
+    let id = BUNDLE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let bundle = Bundle {
+        id,
+        owner: info.sender.to_string(),
+        items: vec![],
+        currency,
+        amount: price,
+        listed: false,
+    };
+    BUNDLE_COUNT.save(deps.storage, &id)?;
+    BUNDLES.save(deps.storage, id, &bundle)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "create_bundle")
+        .add_attribute("bundle_id", id.to_string())
+        .add_attribute("owner", bundle.owner)
+        .add_attribute("currency", bundle.currency.to_string())
+        .add_attribute("amount_requested", bundle.amount.to_string()))
+}
+
+pub fn execute_add_to_bundle(
+    deps: DepsMut,
+    sender: String,
+    cw721_contract: String,
+    token_id: String,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let mut bundle = match BUNDLES.load(deps.storage, bundle_id) {
+        Ok(bundle) => bundle,
+        Err(_) => return Err(ContractError::NoBundle {}),
+    };
+
+    if sender != bundle.owner {
+        return Err(ContractError::InvalidOwner {});
+    }
+    if bundle.listed {
+        return Err(ContractError::BundleListed {});
+    }
+    if CW721_DEPOSITS.has(deps.storage, (&sender, &cw721_contract, &token_id)) {
+        return Err(ContractError::Cw721AlreadyDeposited {});
+    }
+    clear_approval_ask(deps.storage, &cw721_contract, &token_id)?;
+
+    let deposit = Cw721Deposit {
+        owner: sender.clone(),
+        contract: cw721_contract.clone(),
+        token_id: token_id.clone(),
+    };
+    CW721_DEPOSITS.save(
+        deps.storage,
+        (&sender, &cw721_contract, &token_id),
+        &deposit,
+    )?;
+    BUNDLE_ITEMS.save(deps.storage, (&cw721_contract, &token_id), &bundle_id)?;
+
+    bundle.items.push(BundleItem {
+        cw721_contract: cw721_contract.clone(),
+        token_id: token_id.clone(),
+    });
+    BUNDLES.save(deps.storage, bundle_id, &bundle)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "add_to_bundle")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("items", bundle.items.len().to_string()))
+}
+
+pub fn execute_list_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let mut bundle = match BUNDLES.load(deps.storage, bundle_id) {
+        Ok(bundle) => bundle,
+        Err(_) => return Err(ContractError::NoBundle {}),
+    };
+
+    if info.sender != bundle.owner {
+        return Err(ContractError::InvalidOwner {});
+    }
+    if bundle.listed {
+        return Err(ContractError::BundleListed {});
+    }
+    if bundle.items.is_empty() {
+        return Err(ContractError::EmptyBundle {});
+    }
+
+    bundle.listed = true;
+    BUNDLES.save(deps.storage, bundle_id, &bundle)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "list_bundle")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_attribute("items", bundle.items.len().to_string()))
+}
+
+pub fn execute_cancel_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    let bundle = match BUNDLES.load(deps.storage, bundle_id) {
+        Ok(bundle) => bundle,
+        Err(_) => return Err(ContractError::NoBundle {}),
+    };
+
+    if info.sender != bundle.owner {
+        return Err(ContractError::InvalidOwner {});
+    }
+
+    let mut msgs = vec![];
+    for item in &bundle.items {
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &bundle.owner,
+        )?);
+    }
+    remove_bundle(deps, &bundle);
+
+    Ok(Response::new()
+        .add_attribute("execute", "cancel_bundle")
+        .add_attribute("bundle_id", bundle_id.to_string())
+        .add_attribute("owner", bundle.owner)
+        .add_messages(msgs))
+}
+
+pub fn execute_purchase_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match BUNDLES.load(deps.storage, bundle_id) {
+        Ok(bundle) => {
+            match &bundle.currency {
+                Currency::Cw20 { contract } if info.sender == *contract => {}
+                _ => return Err(ContractError::InvalidCoin {}),
+            }
+
+            settle_bundle(deps, bundle, cw20_msg.sender, cw20_msg.amount)
+        }
+        Err(_) => Err(ContractError::NoBundle {}),
+    }
+}
+
+pub fn execute_native_purchase_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    bundle_id: u64,
+) -> Result<Response, ContractError> {
+    match BUNDLES.load(deps.storage, bundle_id) {
+        Ok(bundle) => {
+            let denom = match &bundle.currency {
+                Currency::Native { denom } => denom.clone(),
+                _ => return Err(ContractError::InvalidCoin {}),
+            };
+
+            if info.funds.len() != 1 || info.funds[0].denom != denom {
+                return Err(ContractError::InvalidCoin {});
+            }
+
+            let paid = info.funds[0].amount;
+            settle_bundle(deps, bundle, info.sender.to_string(), paid)
+        }
+        Err(_) => Err(ContractError::NoBundle {}),
+    }
+}
+
+//every item goes to the buyer in the same response, so the bundle sells whole or not at all
+fn settle_bundle(
+    mut deps: DepsMut,
+    bundle: Bundle,
+    buyer: String,
+    paid: Uint128,
+) -> Result<Response, ContractError> {
+    if !bundle.listed {
+        return Err(ContractError::BundleNotListed {});
+    }
+
+    let price = Uint128::new(bundle.amount);
+    if paid < price {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    //royalties are worked out per item on an equal share of the price,
+    //the first item also carries the rounding remainder
+    let count = Uint128::from(bundle.items.len() as u128);
+    let share = price / count;
+    let mut item_price = price - share * count + share;
+
+    let mut msgs = vec![];
+    for item in &bundle.items {
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &buyer,
+        )?);
+        msgs.extend(sale_payout_msgs(
+            deps.branch(),
+            &item.cw721_contract,
+            &item.token_id,
+            &bundle.currency,
+            item_price,
+            &bundle.owner,
+        )?);
+        item_price = share;
+    }
+    remove_bundle(deps, &bundle);
+
+    let mut res = Response::new()
+        .add_attribute("execute", "bundle_purchase")
+        .add_attribute("bundle_id", bundle.id.to_string())
+        .add_attribute("from", bundle.owner)
+        .add_attribute("to", buyer.clone())
+        .add_attribute("currency", bundle.currency.to_string())
+        .add_attribute("amount", price)
+        .add_messages(msgs);
+
+    //send back anything paid over the price
+    if paid > price {
+        res = res.add_message(payment_msg(&bundle.currency, paid - price, &buyer)?);
+    }
+
+    Ok(res)
+}
+
+fn remove_bundle(deps: DepsMut, bundle: &Bundle) {
+    for item in &bundle.items {
+        CW721_DEPOSITS.remove(
+            deps.storage,
+            (&bundle.owner, &item.cw721_contract, &item.token_id),
+        );
+        BUNDLE_ITEMS.remove(deps.storage, (&item.cw721_contract, &item.token_id));
+    }
+    BUNDLES.remove(deps.storage, bundle.id);
+}
+
+//the swap id is assigned here
+pub fn execute_create_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    counterparty: Option<String>,
+    wanted: Vec<TokenRef>,
+    top_up: Option<TopUp>,
+) -> Result<Response, ContractError> {
+    if wanted.is_empty() {
+        return Err(ContractError::EmptySwap {});
+    }
+    //a repeated entry could never be filled, since each token is only received once
+    for (i, item) in wanted.iter().enumerate() {
+        if wanted[..i].contains(item) {
+            return Err(ContractError::DuplicateSwapItem {});
+        }
+    }
+    let counterparty = match counterparty {
+        Some(addr) => Some(deps.api.addr_validate(&addr)?.to_string()),
+        None => None,
+    };
+
+    let id = SWAP_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let swap = Swap {
+        id,
+        maker: info.sender.to_string(),
+        counterparty,
+        offered: vec![],
+        wanted,
+        top_up,
+        taker: None,
+        received: vec![],
+        top_up_paid: false,
+        open: false,
+    };
+    SWAP_COUNT.save(deps.storage, &id)?;
+    SWAPS.save(deps.storage, id, &swap)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "create_swap")
+        .add_attribute("swap_id", id.to_string())
+        .add_attribute("maker", swap.maker)
+        .add_attribute("wanted", swap.wanted.len().to_string()))
+}
+
+pub fn execute_offer_to_swap(
+    deps: DepsMut,
+    sender: String,
+    cw721_contract: String,
+    token_id: String,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    if sender != swap.maker {
+        return Err(ContractError::InvalidOwner {});
+    }
+    if swap.open {
+        return Err(ContractError::SwapOpen {});
+    }
+
+    let item = TokenRef {
+        cw721_contract: cw721_contract.clone(),
+        token_id: token_id.clone(),
+    };
+    if swap.offered.contains(&item) {
+        return Err(ContractError::DuplicateSwapItem {});
+    }
+
+    escrow_swap_item(deps.storage, &sender, &cw721_contract, &token_id, swap_id)?;
+    swap.offered.push(item);
+    SWAPS.save(deps.storage, swap_id, &swap)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "offer_to_swap")
+        .add_attribute("swap_id", swap_id.to_string())
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("offered", swap.offered.len().to_string()))
+}
+
+pub fn execute_open_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    if info.sender != swap.maker {
+        return Err(ContractError::InvalidOwner {});
+    }
+    if swap.open {
+        return Err(ContractError::SwapOpen {});
+    }
+    if swap.offered.is_empty() {
+        return Err(ContractError::EmptySwap {});
+    }
+
+    swap.open = true;
+    SWAPS.save(deps.storage, swap_id, &swap)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "open_swap")
+        .add_attribute("swap_id", swap_id.to_string()))
+}
+
+pub fn execute_fill_swap_nft(
+    deps: DepsMut,
+    sender: String,
+    cw721_contract: String,
+    token_id: String,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    claim_swap(&mut swap, &sender)?;
+    let item = TokenRef {
+        cw721_contract: cw721_contract.clone(),
+        token_id: token_id.clone(),
+    };
+    if !swap.wanted.contains(&item) || swap.received.contains(&item) {
+        return Err(ContractError::UnwantedSwapItem {});
+    }
+
+    escrow_swap_item(deps.storage, &sender, &cw721_contract, &token_id, swap_id)?;
+    swap.received.push(item);
+
+    let res = Response::new()
+        .add_attribute("execute", "fill_swap")
+        .add_attribute("swap_id", swap_id.to_string())
+        .add_attribute("taker", sender)
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id);
+    finish_swap_fill(deps, swap, res)
+}
+
+pub fn execute_native_fill_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let currency = Currency::Native {
+        denom: info.funds[0].denom.clone(),
+    };
+    let paid = info.funds[0].amount;
+    execute_fill_swap_top_up(deps, info.sender.to_string(), currency, paid, swap_id)
+}
+
+pub fn execute_fill_swap_top_up(
+    deps: DepsMut,
+    sender: String,
+    currency: Currency,
+    paid: Uint128,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    claim_swap(&mut swap, &sender)?;
+    let top_up = match &swap.top_up {
+        Some(top_up) if !swap.top_up_paid => top_up.clone(),
+        _ => return Err(ContractError::UnwantedSwapItem {}),
+    };
+    if currency != top_up.currency {
+        return Err(ContractError::InvalidCoin {});
+    }
+    let amount = Uint128::new(top_up.amount);
+    if paid < amount {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    swap.top_up_paid = true;
+
+    let mut res = Response::new()
+        .add_attribute("execute", "fill_swap")
+        .add_attribute("swap_id", swap_id.to_string())
+        .add_attribute("taker", sender.clone())
+        .add_attribute("currency", currency.to_string())
+        .add_attribute("amount", amount);
+
+    //send back anything paid over the top-up
+    if paid > amount {
+        res = res.add_message(payment_msg(&currency, paid - amount, &sender)?);
+    }
+
+    finish_swap_fill(deps, swap, res)
+}
+
+pub fn execute_retract_swap_fill(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    if swap.taker.as_deref() != Some(info.sender.as_str()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let msgs = refund_swap_taker(deps.storage, &mut swap)?;
+    SWAPS.save(deps.storage, swap_id, &swap)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "retract_swap_fill")
+        .add_attribute("swap_id", swap_id.to_string())
+        .add_attribute("taker", info.sender)
+        .add_messages(msgs))
+}
+
+pub fn execute_cancel_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap_id: u64,
+) -> Result<Response, ContractError> {
+    let mut swap = match SWAPS.load(deps.storage, swap_id) {
+        Ok(swap) => swap,
+        Err(_) => return Err(ContractError::NoSwap {}),
+    };
+
+    if info.sender != swap.maker {
+        return Err(ContractError::InvalidOwner {});
+    }
+
+    let mut msgs = refund_swap_taker(deps.storage, &mut swap)?;
+    for item in &swap.offered {
+        release_swap_item(deps.storage, &swap.maker, item);
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &swap.maker,
+        )?);
+    }
+    SWAPS.remove(deps.storage, swap_id);
+
+    Ok(Response::new()
+        .add_attribute("execute", "cancel_swap")
+        .add_attribute("swap_id", swap_id.to_string())
+        .add_attribute("maker", swap.maker)
+        .add_messages(msgs))
+}
+
+//the first fill locks the swap to its sender until they retract
+fn claim_swap(swap: &mut Swap, sender: &str) -> Result<(), ContractError> {
+    if !swap.open {
+        return Err(ContractError::SwapNotOpen {});
+    }
+    if let Some(counterparty) = &swap.counterparty {
+        if counterparty != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+    match &swap.taker {
+        Some(taker) if taker != sender => Err(ContractError::Unauthorized {}),
+        _ => {
+            swap.taker = Some(sender.to_string());
+            Ok(())
+        }
+    }
+}
+
+//settles once the taker has sent everything, otherwise keeps what arrived in escrow
+fn finish_swap_fill(deps: DepsMut, swap: Swap, res: Response) -> Result<Response, ContractError> {
+    let complete =
+        swap.received.len() == swap.wanted.len() && (swap.top_up.is_none() || swap.top_up_paid);
+    if !complete {
+        SWAPS.save(deps.storage, swap.id, &swap)?;
+        return Ok(res);
+    }
+
+    //swaps are peer to peer, so no fee or royalty is taken from the top-up
+    let taker = swap.taker.clone().unwrap_or_default();
+    let mut msgs = vec![];
+    for item in &swap.offered {
+        release_swap_item(deps.storage, &swap.maker, item);
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &taker,
+        )?);
+    }
+    for item in &swap.received {
+        release_swap_item(deps.storage, &taker, item);
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &swap.maker,
+        )?);
+    }
+    if let Some(top_up) = &swap.top_up {
+        msgs.push(payment_msg(
+            &top_up.currency,
+            Uint128::new(top_up.amount),
+            &swap.maker,
+        )?);
+    }
+    SWAPS.remove(deps.storage, swap.id);
+
+    Ok(res
+        .add_attribute("settled", "true")
+        .add_attribute("maker", swap.maker)
+        .add_messages(msgs))
+}
+
+//hands the taker back whatever they sent and reopens the swap to others
+fn refund_swap_taker(storage: &mut dyn Storage, swap: &mut Swap) -> StdResult<Vec<CosmosMsg>> {
+    let taker = match swap.taker.take() {
+        Some(taker) => taker,
+        None => return Ok(vec![]),
+    };
+
+    let mut msgs = vec![];
+    for item in &swap.received {
+        release_swap_item(storage, &taker, item);
+        msgs.push(transfer_nft_msg(
+            &item.cw721_contract,
+            &item.token_id,
+            &taker,
+        )?);
+    }
+    swap.received.clear();
+
+    if let Some(top_up) = &swap.top_up {
+        if swap.top_up_paid {
+            msgs.push(payment_msg(
+                &top_up.currency,
+                Uint128::new(top_up.amount),
+                &taker,
+            )?);
+        }
+    }
+    swap.top_up_paid = false;
+
+    Ok(msgs)
+}
+
+fn escrow_swap_item(
+    storage: &mut dyn Storage,
+    owner: &str,
+    cw721_contract: &str,
+    token_id: &str,
+    swap_id: u64,
+) -> Result<(), ContractError> {
+    if CW721_DEPOSITS.has(storage, (owner, cw721_contract, token_id)) {
+        return Err(ContractError::Cw721AlreadyDeposited {});
+    }
+    clear_approval_ask(storage, cw721_contract, token_id)?;
+
+    let deposit = Cw721Deposit {
+        owner: owner.to_string(),
+        contract: cw721_contract.to_string(),
+        token_id: token_id.to_string(),
+    };
+    CW721_DEPOSITS.save(storage, (owner, cw721_contract, token_id), &deposit)?;
+    SWAP_ITEMS.save(storage, (cw721_contract, token_id), &swap_id)?;
+    Ok(())
+}
+
+//a token sent into escrow can't still be listed from the seller's wallet
+fn clear_approval_ask(
+    storage: &mut dyn Storage,
+    cw721_contract: &str,
+    token_id: &str,
+) -> StdResult<()> {
+    if let Some(ask) = ASKS.may_load(storage, (cw721_contract, token_id))? {
+        if !ask.custodial {
+            ASKS.remove(storage, (cw721_contract, token_id));
+        }
+    }
+    Ok(())
+}
+
+fn release_swap_item(storage: &mut dyn Storage, owner: &str, item: &TokenRef) {
+    CW721_DEPOSITS.remove(storage, (owner, &item.cw721_contract, &item.token_id));
+    SWAP_ITEMS.remove(storage, (&item.cw721_contract, &item.token_id));
+}
+
+pub fn execute_native_bid(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+    expires_at: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let bid = Bid {
+        bidder: info.sender.to_string(),
+        cw721_contract,
+        token_id,
+        currency: Currency::Native {
+            denom: info.funds[0].denom.clone(),
+        },
+        amount: info.funds[0].amount.u128(),
+        expires_at,
+    };
+    execute_place_bid(deps, bid)
+}
+
+pub fn execute_place_bid(deps: DepsMut, bid: Bid) -> Result<Response, ContractError> {
+    if bid.amount == 0 {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let key = (
+        bid.cw721_contract.as_str(),
+        bid.token_id.as_str(),
+        bid.bidder.as_str(),
+    );
+
+    if bids().has(deps.storage, key) {
+        return Err(ContractError::InvalidBid {});
+    }
+    bids().save(deps.storage, key, &bid)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "place_bid")
+        .add_attribute("bidder", bid.bidder.clone())
+        .add_attribute("cw721_contract", bid.cw721_contract.clone())
+        .add_attribute("token_id", bid.token_id.clone())
+        .add_attribute("currency", bid.currency.to_string())
+        .add_attribute("amount", bid.amount.to_string()))
+}
+
+pub fn execute_retract_bid(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let bidder = info.sender.to_string();
+
+    match bids().load(deps.storage, (&cw721_contract, &token_id, &bidder)) {
+        Ok(bid) => {
+            bids().remove(deps.storage, (&cw721_contract, &token_id, &bidder))?;
+
+            let refund_msg = payment_msg(&bid.currency, Uint128::new(bid.amount), &bidder)?;
+
+            Ok(Response::new()
+                .add_attribute("execute", "retract_bid")
+                .add_attribute("bidder", bidder)
+                .add_attribute("cw721_contract", cw721_contract)
+                .add_attribute("token_id", token_id)
+                .add_attribute("amount", bid.amount.to_string())
+                .add_message(refund_msg))
+        }
+        Err(_) => Err(ContractError::NoBidsForTokenID {}),
+    }
+}
+
+//the holder sent the nft along with the AcceptBid hook
+pub fn execute_accept_bid(
+    deps: DepsMut,
+    env: Env,
+    seller: String,
+    cw721_contract: String,
+    token_id: String,
+    bidder: String,
+    terms: BidTerms,
+) -> Result<Response, ContractError> {
+    match bids().load(deps.storage, (&cw721_contract, &token_id, &bidder)) {
+        Ok(bid) => settle_bid(deps, env, seller, bid, &terms),
+        Err(_) => Err(ContractError::NoBidsForTokenID {}),
+    }
+}
+
+//the nft is already listed here, so the seller accepts from custody
+pub fn execute_accept_listed_bid(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+    bidder: String,
+    terms: BidTerms,
+) -> Result<Response, ContractError> {
+    let seller = info.sender.to_string();
+
+    if !CW721_DEPOSITS.has(deps.storage, (&seller, &cw721_contract, &token_id)) {
+        return Err(ContractError::InvalidOwner {});
+    }
+    //bundled and swapped items only leave with the whole bundle or swap
+    if BUNDLE_ITEMS.has(deps.storage, (&cw721_contract, &token_id)) {
+        return Err(ContractError::ItemInBundle {});
+    }
+    if SWAP_ITEMS.has(deps.storage, (&cw721_contract, &token_id)) {
+        return Err(ContractError::ItemInSwap {});
+    }
+
+    match bids().load(deps.storage, (&cw721_contract, &token_id, &bidder)) {
+        Ok(bid) => {
+            let res = settle_bid(deps.branch(), env, seller.clone(), bid, &terms)?;
+
+            CW721_DEPOSITS.remove(deps.storage, (&seller, &cw721_contract, &token_id));
+            ASKS.remove(deps.storage, (&cw721_contract, &token_id));
+
+            Ok(res)
+        }
+        Err(_) => Err(ContractError::NoBidsForTokenID {}),
+    }
+}
+
+fn settle_bid(
+    mut deps: DepsMut,
+    env: Env,
+    seller: String,
+    bid: Bid,
+    terms: &BidTerms,
+) -> Result<Response, ContractError> {
+    if is_expired(&bid.expires_at, &env) {
+        return Err(ContractError::Expired {});
+    }
+    if bid.currency != terms.currency || bid.amount < terms.min_amount {
+        return Err(ContractError::BidTermsChanged {});
+    }
+
+    bids().remove(
+        deps.storage,
+        (&bid.cw721_contract, &bid.token_id, &bid.bidder),
+    )?;
+
+    let nft_msg = transfer_nft_msg(&bid.cw721_contract, &bid.token_id, &bid.bidder)?;
+    let payout_msgs = sale_payout_msgs(
+        deps.branch(),
+        &bid.cw721_contract,
+        &bid.token_id,
+        &bid.currency,
+        Uint128::new(bid.amount),
+        &seller,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "accept_bid")
+        .add_attribute("token_id", bid.token_id)
+        .add_attribute("from", seller)
+        .add_attribute("to", bid.bidder)
+        .add_attribute("currency", bid.currency.to_string())
+        .add_attribute("amount", bid.amount.to_string())
+        .add_message(nft_msg)
+        .add_messages(payout_msgs))
+}
+
+pub fn execute_native_collection_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    price: u128,
+    quantity: u64,
+    traits: Option<Vec<TraitCriterion>>,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let offer = CollectionOffer {
+        id: 0,
+        buyer: info.sender.to_string(),
+        cw721_contract,
+        currency: Currency::Native {
+            denom: info.funds[0].denom.clone(),
+        },
+        price,
+        quantity,
+        traits: traits.unwrap_or_default(),
+    };
+    execute_create_collection_offer(deps, offer, info.funds[0].amount)
+}
+
+//the offer id is assigned here
+pub fn execute_create_collection_offer(
+    deps: DepsMut,
+    mut offer: CollectionOffer,
+    escrowed: Uint128,
+) -> Result<Response, ContractError> {
+    let total = Uint128::new(offer.price)
+        .checked_mul(Uint128::from(offer.quantity))
+        .map_err(|_| ContractError::Overflow {})?;
+    if total.is_zero() || total != escrowed {
+        return Err(ContractError::InvalidEscrowAmount {});
+    }
+
+    offer.id = COLLECTION_OFFER_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    COLLECTION_OFFER_COUNT.save(deps.storage, &offer.id)?;
+    COLLECTION_OFFERS.save(deps.storage, (&offer.cw721_contract, offer.id), &offer)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "create_collection_offer")
+        .add_attribute("offer_id", offer.id.to_string())
+        .add_attribute("buyer", offer.buyer)
+        .add_attribute("cw721_contract", offer.cw721_contract)
+        .add_attribute("currency", offer.currency.to_string())
+        .add_attribute("price", offer.price.to_string())
+        .add_attribute("quantity", offer.quantity.to_string())
+        .add_attribute("traits", offer.traits.len().to_string()))
+}
+
+//any holder fills one unit of the offer by sending a token from the collection
+pub fn execute_fill_collection_offer(
+    mut deps: DepsMut,
+    seller: String,
+    cw721_contract: String,
+    token_id: String,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    match COLLECTION_OFFERS.load(deps.storage, (&cw721_contract, offer_id)) {
+        Ok(mut offer) => {
+            if !offer.traits.is_empty() {
+                let nft_info = NftContract(Addr::unchecked(&cw721_contract))
+                    .nft_info(&deps.querier, token_id.clone())?;
+                if !traits_match(&offer.traits, &nft_info.extension) {
+                    return Err(ContractError::TraitsNotMatched {});
+                }
+            }
+
+            offer.quantity -= 1;
+            if offer.quantity == 0 {
+                COLLECTION_OFFERS.remove(deps.storage, (&cw721_contract, offer_id));
+            } else {
+                COLLECTION_OFFERS.save(deps.storage, (&cw721_contract, offer_id), &offer)?;
+            }
+
+            let nft_msg = transfer_nft_msg(&cw721_contract, &token_id, &offer.buyer)?;
+            let payout_msgs = sale_payout_msgs(
+                deps.branch(),
+                &cw721_contract,
+                &token_id,
+                &offer.currency,
+                Uint128::new(offer.price),
+                &seller,
+            )?;
+
+            Ok(Response::new()
+                .add_attribute("execute", "fill_collection_offer")
+                .add_attribute("offer_id", offer_id.to_string())
+                .add_attribute("token_id", token_id)
+                .add_attribute("from", seller)
+                .add_attribute("to", offer.buyer)
+                .add_attribute("currency", offer.currency.to_string())
+                .add_attribute("amount", offer.price.to_string())
+                .add_attribute("remaining", offer.quantity.to_string())
+                .add_message(nft_msg)
+                .add_messages(payout_msgs))
+        }
+        Err(_) => Err(ContractError::NoCollectionOffer {}),
+    }
+}
+
+pub fn execute_cancel_collection_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw721_contract: String,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    match COLLECTION_OFFERS.load(deps.storage, (&cw721_contract, offer_id)) {
+        Ok(offer) => {
+            if info.sender != offer.buyer {
+                return Err(ContractError::InvalidOwner {});
+            }
+
+            COLLECTION_OFFERS.remove(deps.storage, (&cw721_contract, offer_id));
+
+            //price * quantity was checked when the offer was escrowed
+            let refund = Uint128::new(offer.price) * Uint128::from(offer.quantity);
+            let refund_msg = payment_msg(&offer.currency, refund, &offer.buyer)?;
+
+            Ok(Response::new()
+                .add_attribute("execute", "cancel_collection_offer")
+                .add_attribute("offer_id", offer_id.to_string())
+                .add_attribute("buyer", offer.buyer)
+                .add_attribute("amount", refund)
+                .add_message(refund_msg))
+        }
+        Err(_) => Err(ContractError::NoCollectionOffer {}),
+    }
+}
+
+pub fn execute_create_auction(
+    deps: DepsMut,
+    env: Env,
+    auction: Auction,
+) -> Result<Response, ContractError> {
+    if auction.end_time <= env.block.time {
+        return Err(ContractError::InvalidAuctionEndTime {});
+    }
+    //equal bids would otherwise displace the leader and extend the auction for free
+    if auction.min_increment == 0 {
+        return Err(ContractError::InvalidMinIncrement {});
+    }
+
+    AUCTIONS.save(
+        deps.storage,
+        (&auction.cw721_contract, &auction.token_id),
+        &auction,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "create_auction")
+        .add_attribute("seller", auction.seller)
+        .add_attribute("cw721_contract", auction.cw721_contract)
+        .add_attribute("token_id", auction.token_id)
+        .add_attribute("currency", auction.currency.to_string())
+        .add_attribute("reserve", auction.reserve.to_string())
+        .add_attribute("min_increment", auction.min_increment.to_string())
+        .add_attribute("end_time", auction.end_time.to_string()))
+}
+
+pub fn execute_native_auction_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let bid = Bid {
+        bidder: info.sender.to_string(),
+        cw721_contract,
+        token_id,
+        currency: Currency::Native {
+            denom: info.funds[0].denom.clone(),
+        },
+        amount: info.funds[0].amount.u128(),
+        expires_at: None,
+    };
+    execute_auction_bid(deps, env, bid)
+}
+
+//a new high bid refunds the one it replaces
+pub fn execute_auction_bid(deps: DepsMut, env: Env, bid: Bid) -> Result<Response, ContractError> {
+    let mut auction = match AUCTIONS.load(deps.storage, (&bid.cw721_contract, &bid.token_id)) {
+        Ok(auction) => auction,
+        Err(_) => return Err(ContractError::NoAuction {}),
+    };
+
+    if env.block.time >= auction.end_time {
+        return Err(ContractError::AuctionEnded {});
+    }
+
+    if bid.currency != auction.currency {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let min_bid = match auction.highest_bidder {
+        Some(_) => auction
+            .highest_bid
+            .checked_add(auction.min_increment)
+            .ok_or(ContractError::Overflow {})?,
+        None => auction.reserve,
+    };
+    if bid.amount == 0 || bid.amount < min_bid {
+        return Err(ContractError::BidTooLow {});
+    }
+
+    let mut res = Response::new();
+    if let Some(previous_bidder) = auction.highest_bidder.take() {
+        res = res.add_message(payment_msg(
+            &auction.currency,
+            Uint128::new(auction.highest_bid),
+            &previous_bidder,
+        )?);
+    }
+
+    auction.highest_bidder = Some(bid.bidder.clone());
+    auction.highest_bid = bid.amount;
+
+    //anti-sniping: late bids keep the auction open for another window
+    let extended_end = env.block.time.plus_seconds(auction.extension_window);
+    if extended_end > auction.end_time {
+        auction.end_time = extended_end;
+    }
+    AUCTIONS.save(deps.storage, (&bid.cw721_contract, &bid.token_id), &auction)?;
+
+    Ok(res
+        .add_attribute("execute", "auction_bid")
+        .add_attribute("bidder", bid.bidder)
+        .add_attribute("cw721_contract", bid.cw721_contract)
+        .add_attribute("token_id", bid.token_id)
+        .add_attribute("amount", bid.amount.to_string())
+        .add_attribute("end_time", auction.end_time.to_string()))
+}
+
+pub fn execute_settle_auction(
+    deps: DepsMut,
+    env: Env,
+    cw721_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let auction = match AUCTIONS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(auction) => auction,
+        Err(_) => return Err(ContractError::NoAuction {}),
+    };
+
+    if env.block.time < auction.end_time {
+        return Err(ContractError::AuctionNotEnded {});
+    }
+
+    let msgs = close_auction(deps, &auction)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "settle_auction")
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute(
+            "winner",
+            auction.highest_bidder.unwrap_or_else(|| "none".to_string()),
+        )
+        .add_attribute("amount", auction.highest_bid.to_string())
+        .add_messages(msgs))
+}
+
+//permissionless crank so ended auctions don't wait on the winner or seller
+pub fn execute_settle_auctions(
+    mut deps: DepsMut,
+    env: Env,
+    start_after: Option<TokenRef>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|item| Bound::exclusive((item.cw721_contract.as_str(), item.token_id.as_str())));
+
+    let (page, resume) = read_page(
+        AUCTIONS.range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut settled = 0;
+    for auction in page.iter() {
+        if env.block.time >= auction.end_time {
+            msgs.extend(close_auction(deps.branch(), auction)?);
+            settled += 1;
+        }
+    }
+
+    let mut res = Response::new()
+        .add_attribute("execute", "settle_auctions")
+        .add_attribute("settled", settled.to_string());
+    if let Some(last) = resume {
+        res = res
+            .add_attribute("last_cw721_contract", last.cw721_contract)
+            .add_attribute("last_token_id", last.token_id);
+    }
+    Ok(res.add_messages(msgs))
+}
+
+//cranks read at most limit entries per call, not just act on that many; a full page may have
+//more behind it, so the last entry read is handed back as the place to resume
+fn read_page<K, T: Clone>(
+    entries: impl Iterator<Item = StdResult<(K, T)>>,
+    limit: usize,
+) -> StdResult<(Vec<T>, Option<T>)> {
+    let page = entries
+        .take(limit)
+        .map(|entry| entry.map(|(_, value)| value))
+        .collect::<StdResult<Vec<_>>>()?;
+    let resume = match page.last() {
+        Some(last) if page.len() == limit => Some(last.clone()),
+        _ => None,
+    };
+    Ok((page, resume))
+}
+
+//nft to the winner and funds to the seller, or the nft back to the seller if nobody bid
+fn close_auction(mut deps: DepsMut, auction: &Auction) -> Result<Vec<CosmosMsg>, ContractError> {
+    AUCTIONS.remove(deps.storage, (&auction.cw721_contract, &auction.token_id));
+
+    match &auction.highest_bidder {
+        Some(winner) => {
+            let mut msgs = vec![transfer_nft_msg(
+                &auction.cw721_contract,
+                &auction.token_id,
+                winner,
+            )?];
+            msgs.extend(sale_payout_msgs(
+                deps.branch(),
+                &auction.cw721_contract,
+                &auction.token_id,
+                &auction.currency,
+                Uint128::new(auction.highest_bid),
+                &auction.seller,
+            )?);
+            Ok(msgs)
+        }
+        None => Ok(vec![transfer_nft_msg(
+            &auction.cw721_contract,
+            &auction.token_id,
+            &auction.seller,
+        )?]),
+    }
+}
+
+pub fn execute_create_sealed_auction(
+    deps: DepsMut,
+    env: Env,
+    auction: SealedAuction,
+) -> Result<Response, ContractError> {
+    if auction.commit_end <= env.block.time
+        || auction.reveal_end <= auction.commit_end
+        || auction.non_reveal_penalty_bps > 10_000
+    {
+        return Err(ContractError::InvalidSealedAuction {});
+    }
+    if SEALED_AUCTIONS.has(deps.storage, (&auction.cw721_contract, &auction.token_id)) {
+        return Err(ContractError::SealedAuctionSettling {});
+    }
+
+    SEALED_AUCTIONS.save(
+        deps.storage,
+        (&auction.cw721_contract, &auction.token_id),
+        &auction,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "create_sealed_auction")
+        .add_attribute("seller", auction.seller)
+        .add_attribute("cw721_contract", auction.cw721_contract)
+        .add_attribute("token_id", auction.token_id)
+        .add_attribute("currency", auction.currency.to_string())
+        .add_attribute("commit_end", auction.commit_end.to_string())
+        .add_attribute("reveal_end", auction.reveal_end.to_string()))
+}
+
+pub fn execute_native_commit_sealed_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let currency = Currency::Native {
+        denom: info.funds[0].denom.clone(),
+    };
+    let bid = SealedBid {
+        bidder: info.sender.to_string(),
+        commitment,
+        collateral: info.funds[0].amount.u128(),
+        revealed: None,
+    };
+    execute_commit_sealed_bid(deps, env, cw721_contract, token_id, currency, bid)
+}
+
+//collateral hides the bid, so it only has to cover whatever gets revealed
+pub fn execute_commit_sealed_bid(
+    deps: DepsMut,
+    env: Env,
+    cw721_contract: String,
+    token_id: String,
+    currency: Currency,
+    bid: SealedBid,
+) -> Result<Response, ContractError> {
+    let auction = match SEALED_AUCTIONS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(auction) => auction,
+        Err(_) => return Err(ContractError::NoAuction {}),
+    };
+
+    if env.block.time >= auction.commit_end {
+        return Err(ContractError::NotCommitPhase {});
+    }
+
+    if currency != auction.currency || bid.collateral == 0 {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let key = (
+        cw721_contract.as_str(),
+        token_id.as_str(),
+        bid.bidder.as_str(),
+    );
+    if SEALED_BIDS.has(deps.storage, key) {
+        return Err(ContractError::InvalidBid {});
+    }
+    SEALED_BIDS.save(deps.storage, key, &bid)?;
+
+    Ok(Response::new()
+        .add_attribute("execute", "commit_sealed_bid")
+        .add_attribute("bidder", bid.bidder)
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("collateral", bid.collateral.to_string()))
+}
+
+pub fn execute_reveal_sealed_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_contract: String,
+    token_id: String,
+    amount: u128,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let mut auction = match SEALED_AUCTIONS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(auction) => auction,
+        Err(_) => return Err(ContractError::NoAuction {}),
+    };
+
+    if env.block.time < auction.commit_end || env.block.time >= auction.reveal_end {
+        return Err(ContractError::NotRevealPhase {});
+    }
+
+    let bidder = info.sender.to_string();
+    let key = (cw721_contract.as_str(), token_id.as_str(), bidder.as_str());
+    let mut bid = match SEALED_BIDS.load(deps.storage, key) {
+        Ok(bid) => bid,
+        Err(_) => return Err(ContractError::NoBidsForTokenID {}),
+    };
+
+    if bid.revealed.is_some()
+        || amount > bid.collateral
+        || sealed_bid_commitment(&bidder, &cw721_contract, &token_id, amount, &salt)
+            != bid.commitment
+    {
+        return Err(ContractError::InvalidReveal {});
+    }
+
+    bid.revealed = Some(amount);
+    SEALED_BIDS.save(deps.storage, key, &bid)?;
+
+    //ties go to whoever revealed first
+    if amount >= auction.reserve
+        && (auction.highest_bidder.is_none() || amount > auction.highest_bid)
+    {
+        auction.highest_bidder = Some(bidder.clone());
+        auction.highest_bid = amount;
+        SEALED_AUCTIONS.save(deps.storage, (&cw721_contract, &token_id), &auction)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("execute", "reveal_sealed_bid")
+        .add_attribute("bidder", bidder)
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("amount", amount.to_string()))
+}
+
+//pays the seller, refunds up to limit bidders' collateral less their bid or non-reveal penalty
+pub fn execute_settle_sealed_auction(
+    mut deps: DepsMut,
+    env: Env,
+    cw721_contract: String,
+    token_id: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut auction = match SEALED_AUCTIONS.load(deps.storage, (&cw721_contract, &token_id)) {
+        Ok(auction) => auction,
+        Err(_) => return Err(ContractError::NoAuction {}),
+    };
+
+    if env.block.time < auction.reveal_end {
+        return Err(ContractError::AuctionNotEnded {});
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let sealed_bids: StdResult<Vec<_>> = SEALED_BIDS
+        .prefix((&cw721_contract, &token_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect();
+    let sealed_bids = sealed_bids?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut penalties = Uint128::zero();
+
+    //only the first call moves the nft and pays for the sale
+    if !auction.settled {
+        match &auction.highest_bidder {
+            Some(winner) => {
+                msgs.push(transfer_nft_msg(&cw721_contract, &token_id, winner)?);
+                msgs.extend(sale_payout_msgs(
+                    deps.branch(),
+                    &cw721_contract,
+                    &token_id,
+                    &auction.currency,
+                    Uint128::new(auction.highest_bid),
+                    &auction.seller,
+                )?);
+            }
+            None => msgs.push(transfer_nft_msg(
+                &cw721_contract,
+                &token_id,
+                &auction.seller,
+            )?),
+        }
+        auction.settled = true;
+    }
+
+    for bid in sealed_bids.iter() {
+        SEALED_BIDS.remove(deps.storage, (&cw721_contract, &token_id, &bid.bidder));
+
+        let collateral = Uint128::new(bid.collateral);
+        let kept = match bid.revealed {
+            Some(_) if auction.highest_bidder.as_ref() == Some(&bid.bidder) => {
+                Uint128::new(auction.highest_bid)
+            }
+            Some(_) => Uint128::zero(),
+            None => {
+                let penalty = collateral.multiply_ratio(auction.non_reveal_penalty_bps, 10_000u128);
+                penalties += penalty;
+                penalty
+            }
+        };
+
+        let refund = collateral - kept;
+        if !refund.is_zero() {
+            msgs.push(payment_msg(&auction.currency, refund, &bid.bidder)?);
+        }
+    }
+
+    //non-reveal penalties aren't a sale, so no protocol fee is taken from them
+    if !penalties.is_zero() {
+        msgs.push(payment_msg(&auction.currency, penalties, &auction.seller)?);
+    }
+
+    let pending = SEALED_BIDS
+        .prefix((&cw721_contract, &token_id))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if pending {
+        SEALED_AUCTIONS.save(deps.storage, (&cw721_contract, &token_id), &auction)?;
+    } else {
+        SEALED_AUCTIONS.remove(deps.storage, (&cw721_contract, &token_id));
+    }
+
+    Ok(Response::new()
+        .add_attribute("execute", "settle_sealed_auction")
+        .add_attribute("cw721_contract", cw721_contract)
+        .add_attribute("token_id", token_id)
+        .add_attribute("refunded", sealed_bids.len().to_string())
+        .add_attribute("pending", pending.to_string())
+        .add_attribute(
+            "winner",
+            auction.highest_bidder.unwrap_or_else(|| "none".to_string()),
+        )
+        .add_attribute("amount", auction.highest_bid.to_string())
+        .add_messages(msgs))
+}
+
+//anyone can clear out expired asks and bids, sending the nfts and funds back to their owners
+pub fn execute_prune_expired(
+    deps: DepsMut,
+    env: Env,
+    asks_start_after: Option<TokenRef>,
+    bids_start_after: Option<BidKey>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = asks_start_after
+        .as_ref()
+        .map(|item| Bound::exclusive((item.cw721_contract.as_str(), item.token_id.as_str())));
+    let (asks_page, asks_resume) = read_page(
+        ASKS.range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )?;
+
+    let start = bids_start_after.as_ref().map(|key| {
+        Bound::exclusive((
+            key.cw721_contract.as_str(),
+            key.token_id.as_str(),
+            key.bidder.as_str(),
+        ))
+    });
+    let (bids_page, bids_resume) = read_page(
+        bids().range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )?;
+
+    let expired_asks: Vec<&Offer> = asks_page
+        .iter()
+        .filter(|ask| is_expired(&ask.expires_at, &env))
+        .collect();
+    let expired_bids: Vec<&Bid> = bids_page
+        .iter()
+        .filter(|bid| is_expired(&bid.expires_at, &env))
+        .collect();
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for ask in expired_asks.iter() {
+        ASKS.remove(deps.storage, (&ask.cw721_contract, &ask.token_id));
+        if ask.custodial {
+            CW721_DEPOSITS.remove(
+                deps.storage,
+                (&ask.owner, &ask.cw721_contract, &ask.token_id),
+            );
+            msgs.push(transfer_nft_msg(
+                &ask.cw721_contract,
+                &ask.token_id,
+                &ask.owner,
+            )?);
+        }
+    }
+    for bid in expired_bids.iter() {
+        bids().remove(
+            deps.storage,
+            (&bid.cw721_contract, &bid.token_id, &bid.bidder),
+        )?;
+        msgs.push(payment_msg(
+            &bid.currency,
+            Uint128::new(bid.amount),
+            &bid.bidder,
+        )?);
+    }
+
+    let mut res = Response::new()
+        .add_attribute("execute", "prune_expired")
+        .add_attribute("asks", expired_asks.len().to_string())
+        .add_attribute("bids", expired_bids.len().to_string());
+    if let Some(last) = asks_resume {
+        res = res
+            .add_attribute("last_ask_cw721_contract", last.cw721_contract)
+            .add_attribute("last_ask_token_id", last.token_id);
+    }
+    if let Some(last) = bids_resume {
+        res = res
+            .add_attribute("last_bid_cw721_contract", last.cw721_contract)
+            .add_attribute("last_bid_token_id", last.token_id)
+            .add_attribute("last_bid_bidder", last.bidder);
+    }
+    Ok(res.add_messages(msgs))
+}
+
+//binding the bidder and token stops a copied commitment from being revealed by someone else
+pub fn sealed_bid_commitment(
+    bidder: &str,
+    cw721_contract: &str,
+    token_id: &str,
+    amount: u128,
+    salt: &str,
+) -> Binary {
+    let preimage = format!(
+        "{}:{}:{}:{}:{}",
+        bidder, cw721_contract, token_id, amount, salt
+    );
+    let hash = Sha256::digest(preimage.as_bytes());
+    Binary::from(hash.as_slice())
+}
+
+//fixed price asks are just ask.amount
+pub fn current_price(ask: &Offer, env: &Env) -> Uint128 {
+    let decline = match &ask.decline {
+        Some(decline) => decline,
+        None => return Uint128::new(ask.amount),
+    };
+
+    let now = env.block.time;
+    if now <= decline.start_time {
+        return Uint128::new(decline.start_price);
+    }
+    if now >= decline.end_time {
+        return Uint128::new(decline.end_price);
+    }
+
+    let duration = decline.end_time.seconds() - decline.start_time.seconds();
+    let mut elapsed = now.seconds() - decline.start_time.seconds();
+    if let Some(step) = decline.step_interval {
+        elapsed -= elapsed % step;
+    }
+
+    let drop =
+        Uint128::new(decline.start_price - decline.end_price).multiply_ratio(elapsed, duration);
+    Uint128::new(decline.start_price) - drop
+}
+
+//the seller must still own the token and the marketplace's approval must still stand
+fn approval_listing_valid(deps: Deps, env: &Env, ask: &Offer) -> bool {
+    let nft = NftContract(Addr::unchecked(&ask.cw721_contract));
+    match nft.get_owner(&deps.querier, ask.token_id.clone()) {
+        Ok(res) if res.owner == ask.owner => {
+            let approved = |approvals: &[Approval]| {
+                approvals
+                    .iter()
+                    .any(|approval| approval.spender == env.contract.address)
+            };
+            //a per-token approval or an operator approval over all the owner's tokens both work
+            approved(&res.approvals)
+                || match nft.operators(&deps.querier, res.owner) {
+                    Ok(res) => approved(&res.operators),
+                    Err(_) => false,
+                }
+        }
+        _ => false,
+    }
+}
+
+//deposits on behalf of someone else are only taken from allowlisted operators
+fn check_operator(deps: Deps, sender: &str) -> Result<(), ContractError> {
+    if !OPERATORS.has(deps.storage, sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn is_expired(expires_at: &Option<Expiration>, env: &Env) -> bool {
+    match expires_at {
+        Some(expiration) => expiration.is_expired(&env.block),
+        None => false,
+    }
+}
+
+fn is_started(starts_at: &Option<Scheduled>, env: &Env) -> bool {
+    match starts_at {
+        Some(scheduled) => scheduled.is_triggered(&env.block),
+        None => true,
+    }
+}
+
+//every criterion has to be present in the token's on-chain attributes
+fn traits_match(criteria: &[TraitCriterion], metadata: &nft::contract::Extension) -> bool {
+    let attributes = match metadata {
+        Some(nft::contract::Metadata {
+            attributes: Some(attributes),
+            ..
+        }) => attributes,
+        _ => return false,
+    };
+
+    criteria.iter().all(|criterion| {
+        attributes
+            .iter()
+            .any(|attr| attr.trait_type == criterion.trait_type && attr.value == criterion.value)
+    })
+}
+
+fn transfer_nft_msg(cw721_contract: &str, token_id: &str, recipient: &str) -> StdResult<CosmosMsg> {
+    let exec_msg = nft::contract::ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: token_id.to_string(),
+    };
+
+    Ok(WasmMsg::Execute {
+        contract_addr: cw721_contract.to_string(),
+        msg: to_binary(&exec_msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+//pays the creator's royalty first, then the protocol fee, and the rest to the seller
+fn sale_payout_msgs(
+    deps: DepsMut,
+    cw721_contract: &str,
+    token_id: &str,
+    currency: &Currency,
+    price: Uint128,
+    seller: &str,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let fee = price.multiply_ratio(config.fee_bps, 10_000u128);
+
+    let mut msgs = vec![];
+    let mut remaining = price - fee;
+    if let Some((creator, royalty)) = royalty_for(deps.as_ref(), cw721_contract, token_id, price)? {
+        //never pay out more than what's left after the fee
+        let royalty = royalty.min(remaining);
+        if !royalty.is_zero() {
+            msgs.push(payment_msg(currency, royalty, &creator)?);
+            remaining -= royalty;
+        }
+    }
+    if !fee.is_zero() {
+        msgs.push(payment_msg(currency, fee, &config.fee_recipient)?);
+    }
+    if !remaining.is_zero() {
+        if CREDIT_PROCEEDS.has(deps.storage, seller) {
+            credit_balance(deps.storage, seller, currency, remaining)?;
+        } else {
+            msgs.push(payment_msg(currency, remaining, seller)?);
+        }
+    }
+    Ok(msgs)
+}
+
+fn internal_balance(deps: Deps, owner: &str, currency: &Currency) -> StdResult<Uint128> {
+    match currency {
+        Currency::Native { denom } => Ok(DEPOSITS
+            .may_load(deps.storage, (owner, denom))?
+            .map(|deposit| deposit.amount.amount)
+            .unwrap_or_default()),
+        Currency::Cw20 { contract } => Ok(CW20_DEPOSITS
+            .may_load(deps.storage, (owner, contract))?
+            .map(|deposit| Uint128::new(deposit.amount))
+            .unwrap_or_default()),
+    }
+}
+
+//counts as a deposit into the owner's balance
+fn credit_balance(
+    storage: &mut dyn Storage,
+    owner: &str,
+    currency: &Currency,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    match currency {
+        Currency::Native { denom } => {
+            let mut deposit = DEPOSITS
+                .may_load(storage, (owner, denom))?
+                .unwrap_or(Deposit {
+                    owner: owner.to_string(),
+                    amount: Coin {
+                        denom: denom.clone(),
+                        amount: Uint128::zero(),
+                    },
+                    count: 0,
+                });
+            deposit.amount.amount = deposit
+                .amount
+                .amount
+                .checked_add(amount)
+                .map_err(|_| ContractError::Overflow {})?;
+            deposit.count = deposit
+                .count
+                .checked_add(1)
+                .ok_or(ContractError::Overflow {})?;
+            DEPOSITS.save(storage, (owner, denom), &deposit)?;
+        }
+        Currency::Cw20 { contract } => {
+            let mut deposit = CW20_DEPOSITS
+                .may_load(storage, (owner, contract))?
+                .unwrap_or(Cw20Deposit {
+                    owner: owner.to_string(),
+                    amount: 0,
+                    contract: contract.clone(),
+                    count: 0,
+                });
+            deposit.amount = deposit
+                .amount
+                .checked_add(amount.u128())
+                .ok_or(ContractError::Overflow {})?;
+            deposit.count = deposit
+                .count
+                .checked_add(1)
+                .ok_or(ContractError::Overflow {})?;
+            CW20_DEPOSITS.save(storage, (owner, contract), &deposit)?;
+        }
+    }
+    Ok(())
+}
+
+//counts as a withdrawal from the owner's balance
+fn debit_balance(
+    storage: &mut dyn Storage,
+    owner: &str,
+    currency: &Currency,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    match currency {
+        Currency::Native { denom } => {
+            let mut deposit = match DEPOSITS.may_load(storage, (owner, denom))? {
+                Some(deposit) => deposit,
+                None => return Err(ContractError::InsufficientBalance {}),
+            };
+            deposit.amount.amount = deposit
+                .amount
+                .amount
+                .checked_sub(amount)
+                .map_err(|_| ContractError::InsufficientBalance {})?;
+            deposit.count = deposit.count.saturating_sub(1);
+            DEPOSITS.save(storage, (owner, denom), &deposit)?;
+        }
+        Currency::Cw20 { contract } => {
+            let mut deposit = match CW20_DEPOSITS.may_load(storage, (owner, contract))? {
+                Some(deposit) => deposit,
+                None => return Err(ContractError::InsufficientBalance {}),
+            };
+            deposit.amount = deposit
+                .amount
+                .checked_sub(amount.u128())
+                .ok_or(ContractError::InsufficientBalance {})?;
+            deposit.count = deposit.count.saturating_sub(1);
+            CW20_DEPOSITS.save(storage, (owner, contract), &deposit)?;
+        }
+    }
+    Ok(())
+}
+
+//cw2981 collections answer for themselves, anything else falls back to the registry
+fn royalty_for(
+    deps: Deps,
+    cw721_contract: &str,
+    token_id: &str,
+    price: Uint128,
+) -> StdResult<Option<(String, Uint128)>> {
+    let msg = Cw2981QueryMsg::RoyaltyInfo {
+        token_id: token_id.to_string(),
+        sale_price: price,
+    };
+    let res: StdResult<RoyaltiesInfoResponse> = deps.querier.query_wasm_smart(cw721_contract, &msg);
+    if let Ok(info) = res {
+        return Ok(Some((info.address, info.royalty_amount)));
+    }
+
+    match COLLECTION_ROYALTIES.may_load(deps.storage, cw721_contract)? {
+        Some(royalty) => Ok(Some((
+            royalty.payment_address,
+            price.multiply_ratio(royalty.share_bps, 10_000u128),
+        ))),
+        None => Ok(None),
+    }
+}
+
+fn payment_msg(currency: &Currency, amount: Uint128, recipient: &str) -> StdResult<CosmosMsg> {
+    match currency {
+        Currency::Native { denom } => Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }
+        .into()),
+        Currency::Cw20 { contract } => Ok(WasmMsg::Execute {
+            contract_addr: contract.clone(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into()),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::GetOperators {} => to_binary(&try_query_operators(deps)?),
+        QueryMsg::GetDepositDenoms {} => to_binary(&try_query_deposit_denoms(deps)?),
+        QueryMsg::GetCreditProceeds { address } => to_binary(&CreditProceedsResponse {
+            enabled: CREDIT_PROCEEDS.has(deps.storage, &address),
+        }),
+        QueryMsg::GetCollectionRoyalty { cw721_contract } => {
+            to_binary(&COLLECTION_ROYALTIES.load(deps.storage, &cw721_contract)?)
+        }
+        QueryMsg::GetCw20Deposit { address } => to_binary(&try_query_cw20_deposit(deps, address)?),
+        QueryMsg::GetDeposits { address } => to_binary(&try_query_deposit(deps, address)?),
+        QueryMsg::GetCw721Deposit { address, contract } => {
+            to_binary(&try_query_cw721_deposit(deps, address, contract)?)
+        }
+        QueryMsg::GetBidsForToken {
+            cw721_contract,
+            token_id,
+            start_after,
+            limit,
+        } => to_binary(&try_query_bids_for_token(
+            deps,
+            cw721_contract,
+            token_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetBidsByBidder {
+            bidder,
+            start_after,
+            limit,
+        } => to_binary(&try_query_bids_by_bidder(deps, bidder, start_after, limit)?),
+        QueryMsg::GetCollectionOffers {
+            cw721_contract,
+            start_after,
+            limit,
+        } => to_binary(&try_query_collection_offers(
+            deps,
+            cw721_contract,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetAuction {
+            cw721_contract,
+            token_id,
+        } => to_binary(&AUCTIONS.load(deps.storage, (&cw721_contract, &token_id))?),
+        QueryMsg::GetCurrentPrice {
+            cw721_contract,
+            token_id,
+        } => to_binary(&try_query_current_price(
+            deps,
+            env,
+            cw721_contract,
+            token_id,
+        )?),
+        QueryMsg::GetAsks {
+            cw721_contract,
+            status,
+            start_after,
+            limit,
+        } => to_binary(&try_query_asks(
+            deps,
+            env,
+            cw721_contract,
+            status,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetSealedAuction {
+            cw721_contract,
+            token_id,
+        } => to_binary(&SEALED_AUCTIONS.load(deps.storage, (&cw721_contract, &token_id))?),
+        QueryMsg::GetBundle { bundle_id } => to_binary(&BUNDLES.load(deps.storage, bundle_id)?),
+        QueryMsg::GetSwap { swap_id } => to_binary(&SWAPS.load(deps.storage, swap_id)?),
+    }
+}
+
+pub fn try_query_operators(deps: Deps) -> StdResult<OperatorsResponse> {
+    let operators: StdResult<Vec<_>> = OPERATORS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect();
+
+    Ok(OperatorsResponse {
+        operators: operators?,
+    })
+}
+
+pub fn try_query_deposit_denoms(deps: Deps) -> StdResult<DepositDenomsResponse> {
+    let denoms: StdResult<Vec<_>> = DEPOSIT_DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect();
+
+    Ok(DepositDenomsResponse { denoms: denoms? })
+}
+
+pub fn try_query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
+    let _valid_addr = deps.api.addr_validate(&address)?;
+
+    let res: StdResult<Vec<_>> = DEPOSITS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect();
+
+    let deposits_found = res?;
+
+    if deposits_found.is_empty() {
+        return Err(StdError::generic_err("No deposits found for that address"));
+    }
+
+    let mut deposits: Vec<Deposit> = vec![];
+
+    for deposit in deposits_found {
+        deposits.push(deposit.1);
+    }
+
+    Ok(DepositResponse { deposits })
+}
+
+pub fn try_query_cw20_deposit(deps: Deps, address: String) -> StdResult<Cw20DepositResponse> {
+    let _valid_addr = deps.api.addr_validate(&address)?;
 
     let wrapped_deposits: StdResult<Vec<_>> = CW20_DEPOSITS
         .prefix(&address)
@@ -374,7 +3244,7 @@ pub fn try_query_cw20_deposit(deps: Deps, address: String) -> StdResult<Cw20Depo
 
     let deposits = wrapped_deposits?;
 
-    if deposits.len() == 0 {
+    if deposits.is_empty() {
         return Err(StdError::generic_err(
             "No cw20 deposits exist for that address",
         ));
@@ -405,7 +3275,7 @@ pub fn try_query_cw721_deposit(
 
     let deposits_found = res?;
 
-    if deposits_found.len() == 0 {
+    if deposits_found.is_empty() {
         return Err(StdError::generic_err(
             "No cw721 deposits exist for that address",
         ));
@@ -418,3 +3288,119 @@ pub fn try_query_cw721_deposit(
 
     Ok(Cw721DepositResponse { deposits })
 }
+
+pub fn try_query_bids_for_token(
+    deps: Deps,
+    cw721_contract: String,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let bids: StdResult<Vec<_>> = bids()
+        .prefix((&cw721_contract, &token_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect();
+
+    Ok(BidsResponse { bids: bids? })
+}
+
+pub fn try_query_bids_by_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<TokenRef>,
+    limit: Option<u32>,
+) -> StdResult<BidsResponse> {
+    let _valid_addr = deps.api.addr_validate(&bidder)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|item| Bound::exclusive((item.cw721_contract, item.token_id, bidder.clone())));
+
+    let bids: StdResult<Vec<_>> = bids()
+        .idx
+        .bidder
+        .prefix(bidder)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect();
+
+    Ok(BidsResponse { bids: bids? })
+}
+
+pub fn try_query_collection_offers(
+    deps: Deps,
+    cw721_contract: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CollectionOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let offers: StdResult<Vec<_>> = COLLECTION_OFFERS
+        .prefix(&cw721_contract)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect();
+
+    Ok(CollectionOffersResponse { offers: offers? })
+}
+
+pub fn try_query_asks(
+    deps: Deps,
+    env: Env,
+    cw721_contract: String,
+    status: Option<AskStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AsksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let read = ASKS
+        .prefix(&cw721_contract)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let next_start_after = match read.last() {
+        Some((token_id, _)) if read.len() == limit => Some(token_id.clone()),
+        _ => None,
+    };
+
+    let asks = read
+        .into_iter()
+        .map(|(_, ask)| ask)
+        .filter(|ask| match &status {
+            Some(AskStatus::Upcoming) => !is_started(&ask.starts_at, &env),
+            Some(AskStatus::Live) => {
+                is_started(&ask.starts_at, &env) && !is_expired(&ask.expires_at, &env)
+            }
+            None => true,
+        })
+        .collect();
+
+    Ok(AsksResponse {
+        asks,
+        next_start_after,
+    })
+}
+
+pub fn try_query_current_price(
+    deps: Deps,
+    env: Env,
+    cw721_contract: String,
+    token_id: String,
+) -> StdResult<CurrentPriceResponse> {
+    let ask = ASKS.load(deps.storage, (&cw721_contract, &token_id))?;
+
+    Ok(CurrentPriceResponse {
+        price: current_price(&ask, &env).u128(),
+        currency: ask.currency,
+    })
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,6 +12,15 @@ pub enum ContractError {
     #[error("Invalid Owner")]
     InvalidOwner {},
 
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Fee must be at most 10000 bps")]
+    InvalidFee {},
+
+    #[error("Royalty share must be at most 10000 bps")]
+    InvalidRoyalty {},
+
     #[error("Invalid Coin")]
     InvalidCoin {},
 
@@ -21,12 +30,144 @@ pub enum ContractError {
     #[error("No bids from this sender for this token_id")]
     NoBidsForTokenID {},
 
+    #[error("The bid no longer matches the currency and minimum amount accepted")]
+    BidTermsChanged {},
+
+    #[error("No ask exists for this token_id")]
+    NoAsk {},
+
+    #[error("This ask or bid has expired")]
+    Expired {},
+
+    #[error("This ask is not purchasable until its start time")]
+    AskNotStarted {},
+
+    #[error("Seller must own the token and approve the marketplace to transfer it")]
+    NotApproved {},
+
+    #[error("The approval for this ask was revoked or the token changed owner")]
+    StaleAsk {},
+
+    #[error("This ask is still valid")]
+    AskStillValid {},
+
     #[error("User does not have coins from this cw20 to withdraw")]
     NoCw20ToWithdraw {},
 
+    #[error("Hook message payload is not recognised")]
+    UnknownHookMsg {},
+
+    #[error("No deposit of {denom} to withdraw")]
+    UnknownDenom { denom: String },
+
+    #[error("Arithmetic overflow")]
+    Overflow {},
+
+    #[error("No funds were sent")]
+    NoFunds {},
+
+    #[error("Deposits must be greater than zero")]
+    ZeroAmount {},
+
+    #[error("Deposits of {denom} are not accepted")]
+    DenomNotAllowed { denom: String },
+
+    #[error("Deposits of {denom} must be at least {minimum}")]
+    DepositBelowMinimum { denom: String, minimum: Uint128 },
+
+    #[error("Withdrawal amount exceeds the deposited balance")]
+    InsufficientBalance {},
+
     #[error("Contract does not possess token_id from this cw721 to withdraw")]
     NoCw721ToWithdraw {},
 
     #[error("This Cw721 token is already deposited into the contract")]
     Cw721AlreadyDeposited {},
+
+    #[error("Funds sent are less than the asking price")]
+    InsufficientFunds {},
+
+    #[error("Escrowed funds must equal price times quantity")]
+    InvalidEscrowAmount {},
+
+    #[error("No collection offer with this id for this cw721")]
+    NoCollectionOffer {},
+
+    #[error("Token metadata does not match the offer's trait criteria")]
+    TraitsNotMatched {},
+
+    #[error("No auction exists for this token_id")]
+    NoAuction {},
+
+    #[error("Auction end time must be in the future")]
+    InvalidAuctionEndTime {},
+
+    #[error("Auction min_increment must be greater than zero")]
+    InvalidMinIncrement {},
+
+    #[error("Auction has already ended")]
+    AuctionEnded {},
+
+    #[error("Auction has not ended yet")]
+    AuctionNotEnded {},
+
+    #[error("Bid is below the reserve or the minimum increment")]
+    BidTooLow {},
+
+    #[error("Dutch auction must decline from start_price to end_price over a positive duration")]
+    InvalidPriceDecline {},
+
+    #[error("Sealed auction needs commit_end in the future, reveal_end after it and a penalty of at most 10000 bps")]
+    InvalidSealedAuction {},
+
+    #[error("Sealed auction is not in its commit phase")]
+    NotCommitPhase {},
+
+    #[error("Sealed auction is not in its reveal phase")]
+    NotRevealPhase {},
+
+    #[error("A sealed auction for this token is still refunding its bidders")]
+    SealedAuctionSettling {},
+
+    #[error("Revealed amount and salt don't match the commitment or exceed the collateral")]
+    InvalidReveal {},
+
+    #[error("No bundle exists with this id")]
+    NoBundle {},
+
+    #[error("A bundle needs at least one item before it is listed")]
+    EmptyBundle {},
+
+    #[error("This bundle is already listed")]
+    BundleListed {},
+
+    #[error("This bundle is not listed yet")]
+    BundleNotListed {},
+
+    #[error("This token is part of a bundle")]
+    ItemInBundle {},
+
+    #[error("No swap exists with this id")]
+    NoSwap {},
+
+    #[error("A swap needs at least one offered and one wanted item")]
+    EmptySwap {},
+
+    #[error("This swap is already open")]
+    SwapOpen {},
+
+    #[error("This swap is not open yet")]
+    SwapNotOpen {},
+
+    #[error("This asset isn't wanted by the swap or was already sent")]
+    UnwantedSwapItem {},
+
+    #[error("This token is listed more than once in the swap")]
+    DuplicateSwapItem {},
+
+    #[error("This token is part of a swap")]
+    ItemInSwap {},
+
+    #[error("None of the items could be purchased")]
+    NothingPurchased {},
 }
@@ -1,14 +1,16 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::msg::{Cw20DepositResponse, QueryMsg};
+    use crate::msg::{Cw20DepositResponse, DepositResponse, QueryMsg};
+    use crate::state::{BidTerms, Currency, TokenRef, TopUp, TraitCriterion};
     use anyhow::Error;
-    use cosmwasm_std::{
-        from_binary, to_binary, Addr, Binary, Coin, Empty, StdError, StdResult, Uint128,
-    };
-    use cw20::Cw20Coin;
+    use cosmwasm_std::{to_binary, Addr, Coin, Empty, StdError, StdResult, Uint128};
+    use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+    use cw721::OwnerOfResponse;
 
-    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+    use cw_utils::{Expiration, Scheduled};
+    use serde::de::DeserializeOwned;
 
     use crate::contract;
     use cw20_example::{self};
@@ -17,6 +19,8 @@ mod tests {
 
     const USER: &str = "juno1xdekj862ff8vp9jr98cr2e0gfpcnplgj3p0awr";
     const BUYER: &str = "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h";
+    const BIDDER: &str = "juno1ytwr0sh5xkvlz7ngxl4vd3mkcwqfwp5gts6ax4";
+    const OTHER: &str = "juno1qz8v5zx4hy0rdn3k5pd9ecwt2n7wtlmfjq9kas";
 
     fn mock_app() -> App {
         let init_funds = vec![Coin {
@@ -37,7 +41,29 @@ mod tests {
         app.init_modules(|router, _, storage| {
             router
                 .bank
-                .init_balance(storage, &Addr::unchecked(BUYER.to_string()), init_funds)
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(BUYER.to_string()),
+                    init_funds.clone(),
+                )
+                .unwrap()
+        });
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(BIDDER.to_string()),
+                    init_funds.clone(),
+                )
+                .unwrap()
+        });
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(OTHER.to_string()), init_funds)
                 .unwrap()
         });
 
@@ -66,12 +92,29 @@ mod tests {
         Box::new(contract)
     }
 
+    //plain cw721-base collection without cw2981 royalties
+    fn contract_cw721_base() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps, env, info, msg: nft::contract::ExecuteMsg| {
+                nft::contract::Cw721MetadataContract::default().execute(deps, env, info, msg)
+            },
+            |deps, env, info, msg: cw721_base::InstantiateMsg| {
+                nft::contract::Cw721MetadataContract::default().instantiate(deps, env, info, msg)
+            },
+            |deps, env, msg: cw721_base::QueryMsg| {
+                nft::contract::Cw721MetadataContract::default().query(deps, env, msg)
+            },
+        );
+        Box::new(contract)
+    }
+
     pub struct Suite {
         app: App,
         owner: String,
         nft_marketplace_id: u64,
         cw20_id: u64,
         cw721_id: u64,
+        cw721_base_id: u64,
     }
 
     impl Suite {
@@ -81,6 +124,7 @@ mod tests {
             let nft_marketplace_id = app.store_code(contract_nft_marketplace());
             let cw20_id = app.store_code(contract_cw20());
             let cw721_id = app.store_code(contract_cw721());
+            let cw721_base_id = app.store_code(contract_cw721_base());
 
             Ok(Suite {
                 app,
@@ -88,13 +132,18 @@ mod tests {
                 nft_marketplace_id,
                 cw20_id,
                 cw721_id,
+                cw721_base_id,
             })
         }
 
         fn instantiate_nft_marketplace(&mut self) -> Result<Addr, Error> {
             let code_id = self.nft_marketplace_id;
             let sender = Addr::unchecked(self.owner.clone());
-            let init_msg = crate::msg::InstantiateMsg {};
+            let init_msg = crate::msg::InstantiateMsg {
+                admin: None,
+                fee_bps: 0,
+                fee_recipient: None,
+            };
             let send_funds = vec![];
             let label = "nft_marketplace".to_string();
             let admin = Some(self.owner.clone());
@@ -104,16 +153,22 @@ mod tests {
         }
 
         fn instantiate_cw20(&mut self) -> Result<Addr, Error> {
-            let code_id = self.nft_marketplace_id;
+            let code_id = self.cw20_id;
             let sender = Addr::unchecked(self.owner.clone());
             let init_msg = cw20_base::msg::InstantiateMsg {
                 name: "new_cw20_token".to_string(),
-                symbol: "cw20".to_string(),
+                symbol: "cwtest".to_string(),
                 decimals: 6,
-                initial_balances: vec![Cw20Coin {
-                    address: USER.to_string(),
-                    amount: Uint128::new(1_000_000),
-                }],
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: USER.to_string(),
+                        amount: Uint128::new(1_000_000),
+                    },
+                    Cw20Coin {
+                        address: BUYER.to_string(),
+                        amount: Uint128::new(1_000_000),
+                    },
+                ],
                 mint: None,
                 marketing: None,
             };
@@ -126,12 +181,14 @@ mod tests {
         }
 
         fn instantiate_cw721(&mut self) -> Result<Addr, Error> {
-            let code_id = self.nft_marketplace_id;
+            let code_id = self.cw721_id;
             let sender = Addr::unchecked(self.owner.clone());
-            let init_msg = cw721_base::InstantiateMsg {
+            let init_msg = nft::contract::InstantiateMsg {
                 name: "cw721_project".to_string(),
                 symbol: "cw721".to_string(),
-                minter: String::from(USER.clone()),
+                minter: String::from(USER),
+                royalty_percentage: None,
+                royalty_payment_address: None,
             };
             let send_funds = vec![];
             let label = "new_cw721_contract".to_string();
@@ -141,10 +198,104 @@ mod tests {
                 .instantiate_contract(code_id, sender, &init_msg, &send_funds, label, admin)
         }
 
-        fn smart_query(&self, contract_addr: String, msg: QueryMsg) -> Result<Binary, StdError> {
+        fn instantiate_cw721_base(&mut self) -> Result<Addr, Error> {
+            let code_id = self.cw721_base_id;
+            let sender = Addr::unchecked(self.owner.clone());
+            let init_msg = cw721_base::InstantiateMsg {
+                name: "cw721_project".to_string(),
+                symbol: "cw721".to_string(),
+                minter: String::from(USER),
+            };
+            let send_funds = vec![];
+            let label = "new_cw721_base_contract".to_string();
+            let admin = Some(self.owner.clone());
+
+            self.app
+                .instantiate_contract(code_id, sender, &init_msg, &send_funds, label, admin)
+        }
+
+        fn smart_query<T: DeserializeOwned>(
+            &self,
+            contract_addr: String,
+            msg: QueryMsg,
+        ) -> Result<T, StdError> {
             self.app.wrap().query_wasm_smart(contract_addr, &msg)
         }
 
+        fn mint_nft(&mut self, cw721_addr: &Addr, token_id: &str) -> Result<AppResponse, Error> {
+            self.mint_nft_with_metadata(cw721_addr, token_id, None)
+        }
+
+        fn mint_nft_with_metadata(
+            &mut self,
+            cw721_addr: &Addr,
+            token_id: &str,
+            extension: nft::contract::Extension,
+        ) -> Result<AppResponse, Error> {
+            let msg = nft::contract::ExecuteMsg::Mint(nft::contract::MintMsg {
+                token_id: token_id.to_string(),
+                owner: self.owner.clone(),
+                token_uri: None,
+                extension,
+            });
+
+            self.app.execute_contract(
+                Addr::unchecked(self.owner.clone()),
+                cw721_addr.clone(),
+                &msg,
+                &[],
+            )
+        }
+
+        fn list_nft(
+            &mut self,
+            cw721_addr: &Addr,
+            nft_marketplace_addr: &Addr,
+            token_id: &str,
+            currency: Currency,
+            amount: u128,
+        ) -> Result<AppResponse, Error> {
+            let hook = crate::msg::Cw721HookMsg::Deposit {
+                currency,
+                amount,
+                expires_at: None,
+                starts_at: None,
+            };
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&hook).unwrap(),
+            };
+
+            self.app.execute_contract(
+                Addr::unchecked(self.owner.clone()),
+                cw721_addr.clone(),
+                &msg,
+                &[],
+            )
+        }
+
+        fn query_nft_owner(&self, cw721_addr: &Addr, token_id: &str) -> Result<String, StdError> {
+            let res: OwnerOfResponse = self.app.wrap().query_wasm_smart(
+                cw721_addr.clone(),
+                &nft::contract::QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )?;
+            Ok(res.owner)
+        }
+
+        fn query_cw20_balance(&self, cw20_addr: &Addr, address: &str) -> Result<Uint128, StdError> {
+            let res: BalanceResponse = self.app.wrap().query_wasm_smart(
+                cw20_addr.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )?;
+            Ok(res.balance)
+        }
+
         fn query_balance(&self, address: String, denom: String) -> Result<Coin, StdError> {
             self.app.wrap().query_balance(address, denom)
         }
@@ -284,7 +435,7 @@ mod tests {
     #[test]
     fn test_deposit_and_withdraw_cw20() {
         let mut suite = Suite::init().unwrap();
-        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let _cw20_addr = suite.instantiate_cw20().unwrap();
         let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
 
         //QUERY SENDER ADDRESS BALANCE
@@ -308,7 +459,8 @@ mod tests {
             address: USER.to_string(),
         };
 
-        let res = suite.smart_query(cw20_addr.clone().to_string(), msg);
+        let res: Result<Cw20DepositResponse, StdError> =
+            suite.smart_query(nft_marketplace_addr.clone().to_string(), msg);
 
         match res {
             Err(_) => {}
@@ -318,13 +470,10 @@ mod tests {
         //println!("res: {:?}", res);
 
         //DEPOSIT CW20 TOKENS INTO THE NFT MARKETPLACE
-        let cw20_hook = crate::msg::Cw20HookMsg::Deposit {
-            owner: USER.to_string(),
-            amount: 100,
-        };
+        let cw20_hook = crate::msg::Cw20HookMsg::Deposit {};
         let cw20_msg = cw20::Cw20ReceiveMsg {
-            sender: nft_marketplace_addr.clone().to_string(),
-            amount: Uint128::new(0),
+            sender: USER.to_string(),
+            amount: Uint128::new(100),
             msg: to_binary(&cw20_hook).unwrap(),
         };
         let msg = crate::msg::ExecuteMsg::Receive(cw20_msg);
@@ -354,12 +503,10 @@ mod tests {
         let msg = crate::msg::QueryMsg::GetCw20Deposit {
             address: suite.owner.clone().to_string(),
         };
-        let res = suite
-            .smart_query(cw20_addr.clone().to_string(), msg)
+        let value: Cw20DepositResponse = suite
+            .smart_query(nft_marketplace_addr.clone().to_string(), msg)
             .unwrap();
 
-        let value: Cw20DepositResponse = from_binary(&res).unwrap();
-
         println!("VALUE: {:?}", value);
         assert_eq!(value.deposits[0].owner.clone(), USER.to_string());
 
@@ -418,4 +565,2435 @@ mod tests {
         assert_eq!(res.denom, "utest".to_string());
         assert_eq!(res.amount, Uint128::new(999_999_999)); */
     }
+
+    #[test]
+    fn test_purchase_pays_seller_in_cw20() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //MINT AN NFT TO THE SELLER AND LIST IT FOR 500 CW20
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                500,
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, nft_marketplace_addr.to_string());
+
+        //BUYER SENDS THE CW20 WITH A PURCHASE HOOK
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //NFT GOES TO THE BUYER, CW20 GOES TO THE SELLER
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_500));
+
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_500));
+
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_ref())
+            .unwrap();
+        assert_eq!(balance, Uint128::new(0));
+    }
+
+    #[test]
+    fn test_purchase_with_wrong_cw20_fails() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let other_cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                500,
+            )
+            .unwrap();
+
+        //PAYING WITH A DIFFERENT CW20 OF THE SAME AMOUNT IS REJECTED
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&hook).unwrap(),
+        };
+        let res =
+            suite
+                .app
+                .execute_contract(Addr::unchecked(BUYER), other_cw20_addr.clone(), &msg, &[]);
+        assert!(res.is_err());
+
+        //NOTHING MOVED
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, nft_marketplace_addr.to_string());
+
+        let balance = suite.query_cw20_balance(&other_cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_000));
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_000));
+    }
+
+    #[test]
+    fn test_native_purchase_pays_seller_and_refunds_buyer() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                1_000,
+            )
+            .unwrap();
+
+        //BUYER OVERPAYS BY 500
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_500, "utest")],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_000));
+
+        let res = suite
+            .query_balance(BUYER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(999_999_000));
+
+        let res = suite
+            .query_balance(nft_marketplace_addr.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(0));
+    }
+
+    #[test]
+    fn test_accept_native_bid_on_unlisted_nft() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //BUYER ESCROWS A BID FOR A TOKEN THAT ISN'T LISTED
+        let msg = crate::msg::ExecuteMsg::Bid {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(2_000, "utest")],
+            )
+            .unwrap();
+
+        //HOLDER ACCEPTS BY SENDING THE NFT WITH THE ACCEPT HOOK
+        let hook = crate::msg::Cw721HookMsg::AcceptBid {
+            bidder: BUYER.to_string(),
+            terms: BidTerms {
+                currency: Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                min_amount: 2_000,
+            },
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_002_000));
+
+        let res = suite
+            .query_balance(nft_marketplace_addr.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(0));
+    }
+
+    #[test]
+    fn test_accept_cw20_bid_on_listed_nft() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                500,
+            )
+            .unwrap();
+
+        //BUYER BIDS BELOW THE ASK WITH CW20
+        let hook = crate::msg::Cw20HookMsg::Bid {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: None,
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(400),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //ONLY THE LISTING OWNER CAN ACCEPT FROM CUSTODY
+        let msg = crate::msg::ExecuteMsg::AcceptBid {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            bidder: BUYER.to_string(),
+            terms: BidTerms {
+                currency: Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                min_amount: 400,
+            },
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_400));
+
+        //THE ASK WAS CLEARED WITH THE SALE
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&hook).unwrap(),
+        };
+        let res = suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr, &msg, &[]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_collection_offer_partial_fills() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT1").unwrap();
+        suite.mint_nft(&cw721_addr, "TNT2").unwrap();
+        suite.mint_nft(&cw721_addr, "TNT3").unwrap();
+
+        //BUYER WANTS ANY TWO TOKENS FROM THE COLLECTION AT 1000 EACH
+        let msg = crate::msg::ExecuteMsg::CreateCollectionOffer {
+            cw721_contract: cw721_addr.to_string(),
+            price: 1_000,
+            quantity: 2,
+            traits: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(2_000, "utest")],
+            )
+            .unwrap();
+
+        let hook = crate::msg::Cw721HookMsg::FillCollectionOffer { offer_id: 1 };
+        for token_id in ["TNT1", "TNT3"] {
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&hook).unwrap(),
+            };
+            suite
+                .app
+                .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let owner = suite.query_nft_owner(&cw721_addr, token_id).unwrap();
+            assert_eq!(owner, BUYER.to_string());
+        }
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_002_000));
+
+        //THE OFFER IS USED UP
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT2".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        let res = suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[]);
+        assert!(res.is_err());
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT2").unwrap();
+        assert_eq!(owner, USER.to_string());
+    }
+
+    #[test]
+    fn test_trait_offer_checks_metadata() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        let background = |value: &str| {
+            Some(nft::contract::Metadata {
+                attributes: Some(vec![nft::contract::Trait {
+                    display_type: None,
+                    trait_type: "background".to_string(),
+                    value: value.to_string(),
+                }]),
+                ..nft::contract::Metadata::default()
+            })
+        };
+        suite
+            .mint_nft_with_metadata(&cw721_addr, "SILVER", background("silver"))
+            .unwrap();
+        suite
+            .mint_nft_with_metadata(&cw721_addr, "GOLD", background("gold"))
+            .unwrap();
+        suite.mint_nft(&cw721_addr, "PLAIN").unwrap();
+
+        //BUYER WANTS ANY TOKEN WITH A GOLD BACKGROUND
+        let msg = crate::msg::ExecuteMsg::CreateCollectionOffer {
+            cw721_contract: cw721_addr.to_string(),
+            price: 1_000,
+            quantity: 1,
+            traits: Some(vec![TraitCriterion {
+                trait_type: "background".to_string(),
+                value: "gold".to_string(),
+            }]),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let hook = crate::msg::Cw721HookMsg::FillCollectionOffer { offer_id: 1 };
+        for token_id in ["SILVER", "PLAIN"] {
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&hook).unwrap(),
+            };
+            let res =
+                suite
+                    .app
+                    .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[]);
+            assert!(res.is_err());
+        }
+
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "GOLD".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "GOLD").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SILVER").unwrap();
+        assert_eq!(owner, USER.to_string());
+    }
+
+    #[test]
+    fn test_english_auction_refunds_and_settles() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //SELLER DEPOSITS THE NFT INTO A 100 SECOND AUCTION
+        let end_time = suite.app.block_info().time.plus_seconds(100);
+        let hook = crate::msg::Cw721HookMsg::CreateAuction {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            reserve: 1_000,
+            min_increment: 100,
+            end_time,
+            extension_window: None,
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let bid = crate::msg::ExecuteMsg::BidAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let place_bid = |suite: &mut Suite, bidder: &str, amount: u128| {
+            suite.app.execute_contract(
+                Addr::unchecked(bidder),
+                nft_marketplace_addr.clone(),
+                &bid,
+                &[Coin::new(amount, "utest")],
+            )
+        };
+
+        //BELOW THE RESERVE
+        assert!(place_bid(&mut suite, BUYER, 999).is_err());
+        place_bid(&mut suite, BUYER, 1_000).unwrap();
+
+        //BELOW THE MINIMUM INCREMENT
+        assert!(place_bid(&mut suite, BIDDER, 1_050).is_err());
+        place_bid(&mut suite, BIDDER, 1_100).unwrap();
+
+        //THE OUTBID BUYER GOT THEIR FUNDS BACK
+        let res = suite
+            .query_balance(BUYER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_000));
+
+        //CAN'T SETTLE EARLY
+        let settle = crate::msg::ExecuteMsg::SettleAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &settle,
+            &[],
+        );
+        assert!(res.is_err());
+
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(100);
+        });
+
+        //NO MORE BIDS ONCE IT HAS ENDED
+        assert!(place_bid(&mut suite, BUYER, 2_000).is_err());
+
+        //ANYONE CAN SETTLE
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &settle,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BIDDER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_100));
+
+        let res = suite
+            .query_balance(nft_marketplace_addr.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(0));
+    }
+
+    #[test]
+    fn test_auction_extension_and_settle_crank() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //TWO AUCTIONS ENDING IN 100 SECONDS WITH A 60 SECOND ANTI-SNIPING WINDOW
+        let end_time = suite.app.block_info().time.plus_seconds(100);
+        for token_id in ["TNT1", "TNT2"] {
+            suite.mint_nft(&cw721_addr, token_id).unwrap();
+
+            let hook = crate::msg::Cw721HookMsg::CreateAuction {
+                currency: Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                reserve: 1_000,
+                min_increment: 100,
+                end_time,
+                extension_window: Some(60),
+            };
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&hook).unwrap(),
+            };
+            suite
+                .app
+                .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        //A BID 30 SECONDS BEFORE THE END PUSHES IT OUT TO 60 SECONDS FROM NOW
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(70);
+        });
+        let msg = crate::msg::ExecuteMsg::BidAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT1".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let msg = QueryMsg::GetAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT1".to_string(),
+        };
+        let auction: crate::state::Auction = suite
+            .smart_query(nft_marketplace_addr.to_string(), msg)
+            .unwrap();
+        assert_eq!(auction.end_time, end_time.plus_seconds(30));
+
+        //ONLY THE UNEXTENDED AUCTION HAS ENDED HERE
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(40);
+        });
+        let crank = crate::msg::ExecuteMsg::SettleAuctions {
+            start_after: None,
+            limit: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BIDDER),
+                nft_marketplace_addr.clone(),
+                &crank,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT2").unwrap();
+        assert_eq!(owner, USER.to_string());
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT1").unwrap();
+        assert_eq!(owner, nft_marketplace_addr.to_string());
+
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(20);
+        });
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BIDDER),
+                nft_marketplace_addr.clone(),
+                &crank,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT1").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_000));
+    }
+
+    #[test]
+    fn test_dutch_auction_cw20_purchase_refunds_difference() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //PRICE FALLS FROM 1000 TO 200 OVER 100 SECONDS
+        let start_time = suite.app.block_info().time;
+        let hook = crate::msg::Cw721HookMsg::CreateDutchAuction {
+            currency: Currency::Cw20 {
+                contract: cw20_addr.to_string(),
+            },
+            start_price: 1_000,
+            end_price: 200,
+            start_time,
+            end_time: start_time.plus_seconds(100),
+            step_interval: None,
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(50);
+        });
+
+        let msg = QueryMsg::GetCurrentPrice {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res: crate::msg::CurrentPriceResponse = suite
+            .smart_query(nft_marketplace_addr.to_string(), msg)
+            .unwrap();
+        assert_eq!(res.price, 600);
+
+        //BUYER SENDS THE START PRICE AND GETS THE DIFFERENCE BACK
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(1_000),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_600));
+
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_400));
+    }
+
+    #[test]
+    fn test_sealed_auction_full_lifecycle() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //SELLER DEPOSITS THE NFT INTO A SEALED AUCTION WITH A 10% NON-REVEAL PENALTY
+        let now = suite.app.block_info().time;
+        let hook = crate::msg::Cw721HookMsg::CreateSealedAuction {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            reserve: 500,
+            commit_end: now.plus_seconds(100),
+            reveal_end: now.plus_seconds(200),
+            non_reveal_penalty_bps: Some(1_000),
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //EVERY BIDDER COMMITS A HASH AND OVER-COLLATERALISES TO HIDE THEIR BID
+        let commits = [
+            (BUYER, 1_200u128, "buyer-salt", 1_500u128),
+            (BIDDER, 1_000, "bidder-salt", 2_000),
+            (OTHER, 800, "other-salt", 1_000),
+        ];
+        for (bidder, amount, salt, collateral) in commits {
+            let msg = crate::msg::ExecuteMsg::CommitSealedBid {
+                cw721_contract: cw721_addr.to_string(),
+                token_id: "TNT".to_string(),
+                commitment: contract::sealed_bid_commitment(
+                    bidder,
+                    cw721_addr.as_str(),
+                    "TNT",
+                    amount,
+                    salt,
+                ),
+            };
+            suite
+                .app
+                .execute_contract(
+                    Addr::unchecked(bidder),
+                    nft_marketplace_addr.clone(),
+                    &msg,
+                    &[Coin::new(collateral, "utest")],
+                )
+                .unwrap();
+        }
+
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(100);
+        });
+
+        let reveal = |suite: &mut Suite, bidder: &str, amount: u128, salt: &str| {
+            let msg = crate::msg::ExecuteMsg::RevealSealedBid {
+                cw721_contract: cw721_addr.to_string(),
+                token_id: "TNT".to_string(),
+                amount,
+                salt: salt.to_string(),
+            };
+            suite.app.execute_contract(
+                Addr::unchecked(bidder),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+        };
+
+        //A REVEAL THAT DOESN'T MATCH THE COMMITMENT IS REJECTED
+        assert!(reveal(&mut suite, BUYER, 1_300, "buyer-salt").is_err());
+        reveal(&mut suite, BUYER, 1_200, "buyer-salt").unwrap();
+        reveal(&mut suite, BIDDER, 1_000, "bidder-salt").unwrap();
+
+        //CAN'T SETTLE BEFORE THE REVEAL PHASE ENDS
+        let settle = crate::msg::ExecuteMsg::SettleSealedAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            limit: Some(1),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(OTHER),
+            nft_marketplace_addr.clone(),
+            &settle,
+            &[],
+        );
+        assert!(res.is_err());
+
+        suite.app.update_block(|block| {
+            block.time = block.time.plus_seconds(100);
+        });
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OTHER),
+                nft_marketplace_addr.clone(),
+                &settle,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        //THE FIRST PAGE ONLY REFUNDED ONE BIDDER, THE REST COME ON THE NEXT CALL
+        let msg = QueryMsg::GetSealedAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let auction: crate::state::SealedAuction = suite
+            .smart_query(nft_marketplace_addr.to_string(), msg.clone())
+            .unwrap();
+        assert!(auction.settled);
+
+        let settle = crate::msg::ExecuteMsg::SettleSealedAuction {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            limit: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OTHER),
+                nft_marketplace_addr.clone(),
+                &settle,
+                &[],
+            )
+            .unwrap();
+
+        let res: StdResult<crate::state::SealedAuction> =
+            suite.smart_query(nft_marketplace_addr.to_string(), msg);
+        assert!(res.is_err());
+
+        //SELLER GETS THE WINNING BID PLUS THE NON-REVEAL PENALTY
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_300));
+
+        let res = suite
+            .query_balance(BUYER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(999_998_800));
+
+        let res = suite
+            .query_balance(BIDDER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_000));
+
+        let res = suite
+            .query_balance(OTHER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(999_999_900));
+
+        let res = suite
+            .query_balance(nft_marketplace_addr.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::zero());
+    }
+
+    #[test]
+    fn test_purchase_splits_protocol_fee() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //ONLY THE ADMIN CAN SET THE FEE
+        let msg = crate::msg::ExecuteMsg::UpdateConfig {
+            admin: None,
+            fee_bps: Some(500),
+            fee_recipient: Some(OTHER.to_string()),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let config: crate::state::Config = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                crate::msg::QueryMsg::GetConfig {},
+            )
+            .unwrap();
+        assert_eq!(config.admin, USER.to_string());
+        assert_eq!(config.fee_bps, 500);
+        assert_eq!(config.fee_recipient, OTHER.to_string());
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                1_000,
+            )
+            .unwrap();
+
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(1_000),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        //5% TO THE FEE RECIPIENT, THE REST TO THE SELLER
+        let balance = suite.query_cw20_balance(&cw20_addr, OTHER).unwrap();
+        assert_eq!(balance, Uint128::new(50));
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_950));
+
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_str())
+            .unwrap();
+        assert_eq!(balance, Uint128::zero());
+    }
+
+    #[test]
+    fn test_collection_royalty_registry_fallback() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721_base().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //ONLY THE COLLECTION'S MINTER CAN REGISTER A ROYALTY
+        let msg = crate::msg::ExecuteMsg::SetCollectionRoyalty {
+            cw721_contract: cw721_addr.to_string(),
+            payment_address: OTHER.to_string(),
+            share_bps: 1_000,
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //THE SELLER IS NOT THE CREATOR
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        let msg = nft::contract::ExecuteMsg::TransferNft {
+            recipient: BIDDER.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let hook = crate::msg::Cw721HookMsg::Deposit {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            amount: 1_000,
+            expires_at: None,
+            starts_at: None,
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BIDDER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        //10% TO THE CREATOR, THE REST TO THE SELLER
+        let res = suite
+            .query_balance(OTHER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_100));
+
+        let res = suite
+            .query_balance(BIDDER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_900));
+    }
+
+    #[test]
+    fn test_cw2981_royalty_from_nft_metadata() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //THE REGISTRY IS IGNORED FOR COLLECTIONS THAT ANSWER CW2981 THEMSELVES
+        let msg = crate::msg::ExecuteMsg::SetCollectionRoyalty {
+            cw721_contract: cw721_addr.to_string(),
+            payment_address: BIDDER.to_string(),
+            share_bps: 2_000,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //A ROYALTY OVER 100% CAN'T BE MINTED
+        let res = suite.mint_nft_with_metadata(
+            &cw721_addr,
+            "TNT",
+            Some(nft::contract::Metadata {
+                royalty_percentage: Some(101),
+                royalty_payment_address: Some(OTHER.to_string()),
+                ..nft::contract::Metadata::default()
+            }),
+        );
+        assert!(res.is_err());
+
+        suite
+            .mint_nft_with_metadata(
+                &cw721_addr,
+                "TNT",
+                Some(nft::contract::Metadata {
+                    royalty_percentage: Some(10),
+                    royalty_payment_address: Some(OTHER.to_string()),
+                    ..nft::contract::Metadata::default()
+                }),
+            )
+            .unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                1_000,
+            )
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let res = suite
+            .query_balance(OTHER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_100));
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_900));
+
+        let res = suite
+            .query_balance(BIDDER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_000));
+    }
+
+    #[test]
+    fn test_update_and_cancel_ask_returns_nft() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                1_000,
+            )
+            .unwrap();
+
+        //SELLER REPRICES THE LISTING IN CW20
+        let msg = crate::msg::ExecuteMsg::UpdateAsk {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            price: 500,
+            currency: Currency::Cw20 {
+                contract: cw20_addr.to_string(),
+            },
+            expires_at: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //THE OLD NATIVE PRICE IS GONE
+        let purchase = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &purchase,
+            &[Coin::new(1_000, "utest")],
+        );
+        assert!(res.is_err());
+
+        let msg = crate::msg::ExecuteMsg::CancelAsk {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, USER.to_string());
+
+        //NOTHING LEFT TO BUY
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "TNT".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&hook).unwrap(),
+        };
+        let res = suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr, &msg, &[]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_prune_expired_returns_nft_and_bid() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //LISTING EXPIRES IN 10 BLOCKS
+        let expires_at = Expiration::AtHeight(suite.app.block_info().height + 10);
+        let hook = crate::msg::Cw721HookMsg::Deposit {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            amount: 1_000,
+            expires_at: Some(expires_at),
+            starts_at: None,
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //BID EXPIRES IN 60 SECONDS
+        let msg = crate::msg::ExecuteMsg::Bid {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: Some(Expiration::AtTime(
+                suite.app.block_info().time.plus_seconds(60),
+            )),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BIDDER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(800, "utest")],
+            )
+            .unwrap();
+
+        suite.app.update_block(|block| {
+            block.height += 10;
+            block.time = block.time.plus_seconds(60);
+        });
+
+        //STALE PRICE CAN'T BE BOUGHT
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[Coin::new(1_000, "utest")],
+        );
+        assert!(res.is_err());
+
+        //ANYONE CAN PRUNE
+        let msg = crate::msg::ExecuteMsg::PruneExpired {
+            asks_start_after: None,
+            bids_start_after: None,
+            limit: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OTHER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, USER.to_string());
+
+        let res = suite
+            .query_balance(BIDDER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_000));
+
+        let res = suite
+            .query_balance(nft_marketplace_addr.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::zero());
+    }
+
+    #[test]
+    fn test_scheduled_drop_opens_at_height() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        //LISTED NOW, PURCHASABLE IN 5 BLOCKS
+        let hook = crate::msg::Cw721HookMsg::Deposit {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            amount: 1_000,
+            expires_at: None,
+            starts_at: Some(Scheduled::AtHeight(suite.app.block_info().height + 5)),
+        };
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let res: crate::msg::AsksResponse = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                QueryMsg::GetAsks {
+                    cw721_contract: cw721_addr.to_string(),
+                    status: Some(crate::msg::AskStatus::Upcoming),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.asks.len(), 1);
+
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[Coin::new(1_000, "utest")],
+        );
+        assert!(res.is_err());
+
+        suite.app.update_block(|block| {
+            block.height += 5;
+        });
+
+        let res: crate::msg::AsksResponse = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                QueryMsg::GetAsks {
+                    cw721_contract: cw721_addr.to_string(),
+                    status: Some(crate::msg::AskStatus::Live),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.asks.len(), 1);
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+    }
+
+    #[test]
+    fn test_approval_listing_purchase_and_revocation() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite.mint_nft(&cw721_addr, "TNT2").unwrap();
+
+        let list = |token_id: &str| crate::msg::ExecuteMsg::ListWithApproval {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: token_id.to_string(),
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            price: 1_000,
+            expires_at: None,
+            starts_at: None,
+        };
+        let approve = |token_id: &str| nft::contract::ExecuteMsg::Approve {
+            spender: nft_marketplace_addr.to_string(),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+
+        //CAN'T LIST WITHOUT APPROVING THE MARKETPLACE
+        let res = suite.app.execute_contract(
+            Addr::unchecked(USER),
+            nft_marketplace_addr.clone(),
+            &list("TNT"),
+            &[],
+        );
+        assert!(res.is_err());
+
+        for token_id in ["TNT", "TNT2"] {
+            suite
+                .app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw721_addr.clone(),
+                    &approve(token_id),
+                    &[],
+                )
+                .unwrap();
+            suite
+                .app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    nft_marketplace_addr.clone(),
+                    &list(token_id),
+                    &[],
+                )
+                .unwrap();
+        }
+
+        //THE SELLER KEEPS THE NFT UNTIL IT SELLS
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, USER.to_string());
+
+        let purchase = |token_id: &str| crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: token_id.to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &purchase("TNT"),
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_000));
+
+        //THE SELLER REVOKES THE SECOND APPROVAL
+        let msg = nft::contract::ExecuteMsg::Revoke {
+            spender: nft_marketplace_addr.to_string(),
+            token_id: "TNT2".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &purchase("TNT2"),
+            &[Coin::new(1_000, "utest")],
+        );
+        assert!(res.is_err());
+
+        //ANYONE CAN CLEAR THE STALE LISTING
+        let msg = crate::msg::ExecuteMsg::InvalidateAsk {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT2".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OTHER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let res: crate::msg::AsksResponse = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                QueryMsg::GetAsks {
+                    cw721_contract: cw721_addr.to_string(),
+                    status: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.asks.is_empty());
+    }
+
+    #[test]
+    fn test_purchase_with_cw20_allowance() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                1_000,
+            )
+            .unwrap();
+
+        let purchase = crate::msg::ExecuteMsg::PurchaseWithAllowance {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+            cw20_contract: cw20_addr.to_string(),
+        };
+
+        //NO ALLOWANCE YET
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &purchase,
+            &[],
+        );
+        assert!(res.is_err());
+
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(5_000),
+            expires: None,
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &purchase,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        //ONLY THE PRICE WAS PULLED
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_000));
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_001_000));
+
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_str())
+            .unwrap();
+        assert_eq!(balance, Uint128::zero());
+    }
+
+    #[test]
+    fn test_bundle_sells_items_from_two_collections() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let other_cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "SWORD").unwrap();
+        suite.mint_nft(&other_cw721_addr, "SHIELD").unwrap();
+
+        let msg = crate::msg::ExecuteMsg::CreateBundle {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            price: 1_000,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //SELLER SENDS BOTH ITEMS INTO BUNDLE 1
+        for (addr, token_id) in [(&cw721_addr, "SWORD"), (&other_cw721_addr, "SHIELD")] {
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&crate::msg::Cw721HookMsg::AddToBundle { bundle_id: 1 }).unwrap(),
+            };
+            suite
+                .app
+                .execute_contract(Addr::unchecked(USER), addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        //NOT FOR SALE UNTIL LISTED
+        let purchase = crate::msg::ExecuteMsg::PurchaseBundle { bundle_id: 1 };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &purchase,
+            &[Coin::new(1_000, "utest")],
+        );
+        assert!(res.is_err());
+
+        let msg = crate::msg::ExecuteMsg::ListBundle { bundle_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //A SINGLE ITEM CAN'T BE PULLED OUT OF THE BUNDLE
+        let msg = crate::msg::ExecuteMsg::WithdrawNft {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "SWORD".to_string(),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(USER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &purchase,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SWORD").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+        let owner = suite.query_nft_owner(&other_cw721_addr, "SHIELD").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let res = suite
+            .query_balance(USER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_000));
+
+        let res: StdResult<crate::state::Bundle> = suite.smart_query(
+            nft_marketplace_addr.to_string(),
+            crate::msg::QueryMsg::GetBundle { bundle_id: 1 },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_cancel_bundle_returns_every_item() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "SWORD").unwrap();
+        suite.mint_nft(&cw721_addr, "SHIELD").unwrap();
+
+        let msg = crate::msg::ExecuteMsg::CreateBundle {
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            price: 1_000,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        for token_id in ["SWORD", "SHIELD"] {
+            let msg = nft::contract::ExecuteMsg::SendNft {
+                contract: nft_marketplace_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&crate::msg::Cw721HookMsg::AddToBundle { bundle_id: 1 }).unwrap(),
+            };
+            suite
+                .app
+                .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let bundle: crate::state::Bundle = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                crate::msg::QueryMsg::GetBundle { bundle_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(bundle.items.len(), 2);
+        assert!(!bundle.listed);
+
+        //ONLY THE SELLER CAN CANCEL
+        let msg = crate::msg::ExecuteMsg::CancelBundle { bundle_id: 1 };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SWORD").unwrap();
+        assert_eq!(owner, USER.to_string());
+        let owner = suite.query_nft_owner(&cw721_addr, "SHIELD").unwrap();
+        assert_eq!(owner, USER.to_string());
+    }
+
+    #[test]
+    fn test_swap_settles_nft_and_cw20_top_up() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let other_cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "SWORD").unwrap();
+        suite.mint_nft(&other_cw721_addr, "SHIELD").unwrap();
+        let msg = nft::contract::ExecuteMsg::TransferNft {
+            recipient: BUYER.to_string(),
+            token_id: "SHIELD".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), other_cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //USER OFFERS THE SWORD FOR BUYER'S SHIELD AND 500 CW20
+        let msg = crate::msg::ExecuteMsg::CreateSwap {
+            counterparty: Some(BUYER.to_string()),
+            wanted: vec![TokenRef {
+                cw721_contract: other_cw721_addr.to_string(),
+                token_id: "SHIELD".to_string(),
+            }],
+            top_up: Some(TopUp {
+                currency: Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                amount: 500,
+            }),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "SWORD".to_string(),
+            msg: to_binary(&crate::msg::Cw721HookMsg::OfferToSwap { swap_id: 1 }).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::OpenSwap { swap_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //BUYER SENDS THE SHIELD, NOTHING SETTLES YET
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "SHIELD".to_string(),
+            msg: to_binary(&crate::msg::Cw721HookMsg::FillSwap { swap_id: 1 }).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), other_cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SWORD").unwrap();
+        assert_eq!(owner, nft_marketplace_addr.to_string());
+
+        //THE TOP-UP COMPLETES THE TRADE
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(500),
+            msg: to_binary(&crate::msg::Cw20HookMsg::FillSwap { swap_id: 1 }).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SWORD").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+        let owner = suite.query_nft_owner(&other_cw721_addr, "SHIELD").unwrap();
+        assert_eq!(owner, USER.to_string());
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_500));
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_500));
+    }
+
+    #[test]
+    fn test_swap_retract_and_cancel_refund_both_sides() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "SWORD").unwrap();
+        suite.mint_nft(&cw721_addr, "SHIELD").unwrap();
+        suite.mint_nft(&cw721_addr, "HELMET").unwrap();
+        for token_id in ["SHIELD", "HELMET"] {
+            let msg = nft::contract::ExecuteMsg::TransferNft {
+                recipient: BUYER.to_string(),
+                token_id: token_id.to_string(),
+            };
+            suite
+                .app
+                .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        //USER WANTS BOTH OF BUYER'S ITEMS AND 300 UTEST, ANYONE MAY FILL
+        let msg = crate::msg::ExecuteMsg::CreateSwap {
+            counterparty: None,
+            wanted: vec![
+                TokenRef {
+                    cw721_contract: cw721_addr.to_string(),
+                    token_id: "SHIELD".to_string(),
+                },
+                TokenRef {
+                    cw721_contract: cw721_addr.to_string(),
+                    token_id: "HELMET".to_string(),
+                },
+            ],
+            top_up: Some(TopUp {
+                currency: Currency::Native {
+                    denom: "utest".to_string(),
+                },
+                amount: 300,
+            }),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "SWORD".to_string(),
+            msg: to_binary(&crate::msg::Cw721HookMsg::OfferToSwap { swap_id: 1 }).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::OpenSwap { swap_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //BUYER SENDS ONE ITEM AND THE TOP-UP, THEN BACKS OUT
+        let msg = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "SHIELD".to_string(),
+            msg: to_binary(&crate::msg::Cw721HookMsg::FillSwap { swap_id: 1 }).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw721_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::FillSwap { swap_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(300, "utest")],
+            )
+            .unwrap();
+
+        let swap: crate::state::Swap = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                crate::msg::QueryMsg::GetSwap { swap_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(swap.taker, Some(BUYER.to_string()));
+        assert_eq!(swap.received.len(), 1);
+        assert!(swap.top_up_paid);
+
+        let msg = crate::msg::ExecuteMsg::RetractSwapFill { swap_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SHIELD").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+        let res = suite
+            .query_balance(BUYER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_000_000));
+
+        let msg = crate::msg::ExecuteMsg::CancelSwap { swap_id: 1 };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "SWORD").unwrap();
+        assert_eq!(owner, USER.to_string());
+        let res: StdResult<crate::state::Swap> = suite.smart_query(
+            nft_marketplace_addr.to_string(),
+            crate::msg::QueryMsg::GetSwap { swap_id: 1 },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_purchase_many_with_cw20_refunds_unspent() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        for (token_id, price) in [("ONE", 100), ("TWO", 200), ("THREE", 300)] {
+            suite.mint_nft(&cw721_addr, token_id).unwrap();
+            suite
+                .list_nft(
+                    &cw721_addr,
+                    &nft_marketplace_addr,
+                    token_id,
+                    Currency::Cw20 {
+                        contract: cw20_addr.to_string(),
+                    },
+                    price,
+                )
+                .unwrap();
+        }
+
+        //THE SELLER BUYS BACK THREE BEFORE THE CART GOES THROUGH
+        let hook = crate::msg::Cw20HookMsg::Purchase {
+            token_id: "THREE".to_string(),
+            cw721_contract: cw721_addr.to_string(),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(300),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let items: Vec<TokenRef> = ["ONE", "TWO", "THREE"]
+            .iter()
+            .map(|token_id| TokenRef {
+                cw721_contract: cw721_addr.to_string(),
+                token_id: token_id.to_string(),
+            })
+            .collect();
+
+        //ALL OR NOTHING FAILS ON THE SOLD ITEM
+        let hook = crate::msg::Cw20HookMsg::PurchaseMany {
+            items: items.clone(),
+            best_effort: None,
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(600),
+            msg: to_binary(&hook).unwrap(),
+        };
+        let res = suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[]);
+        assert!(res.is_err());
+
+        let hook = crate::msg::Cw20HookMsg::PurchaseMany {
+            items,
+            best_effort: Some(true),
+        };
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(600),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let owner = suite.query_nft_owner(&cw721_addr, "ONE").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+        let owner = suite.query_nft_owner(&cw721_addr, "TWO").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+        let owner = suite.query_nft_owner(&cw721_addr, "THREE").unwrap();
+        assert_eq!(owner, USER.to_string());
+
+        //ONLY 300 OF THE 600 WAS SPENT
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_700));
+        //THE BUY BACK OF THREE NETS OUT FOR THE SELLER
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_300));
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_str())
+            .unwrap();
+        assert_eq!(balance, Uint128::zero());
+    }
+
+    #[test]
+    fn test_withdraw_cw20_transfers_to_depositor() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        let hook = crate::msg::Cw20HookMsg::Deposit {};
+        let msg = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(1_000),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &msg, &[])
+            .unwrap();
+
+        //NOBODY ELSE CAN TOUCH BUYER'S BALANCE
+        let msg = crate::msg::ExecuteMsg::WithdrawCw20 {
+            cw20_contract: cw20_addr.to_string(),
+            amount: 1_000,
+            recipient: Some(USER.to_string()),
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(USER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        //MORE THAN THE BALANCE
+        let msg = crate::msg::ExecuteMsg::WithdrawCw20 {
+            cw20_contract: cw20_addr.to_string(),
+            amount: 1_001,
+            recipient: None,
+        };
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        let msg = crate::msg::ExecuteMsg::WithdrawCw20 {
+            cw20_contract: cw20_addr.to_string(),
+            amount: 400,
+            recipient: Some(OTHER.to_string()),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::WithdrawCw20 {
+            cw20_contract: cw20_addr.to_string(),
+            amount: 600,
+            recipient: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let balance = suite.query_cw20_balance(&cw20_addr, OTHER).unwrap();
+        assert_eq!(balance, Uint128::new(400));
+        let balance = suite.query_cw20_balance(&cw20_addr, BUYER).unwrap();
+        assert_eq!(balance, Uint128::new(999_600));
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_str())
+            .unwrap();
+        assert_eq!(balance, Uint128::zero());
+    }
+
+    #[test]
+    fn test_operator_lists_on_behalf_of_seller() {
+        let mut suite = Suite::init().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+
+        let hook = crate::msg::Cw721HookMsg::DepositFor {
+            owner: BIDDER.to_string(),
+            currency: Currency::Native {
+                denom: "utest".to_string(),
+            },
+            amount: 1_000,
+            expires_at: None,
+            starts_at: None,
+        };
+        let send = nft::contract::ExecuteMsg::SendNft {
+            contract: nft_marketplace_addr.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&hook).unwrap(),
+        };
+
+        //NOT AN OPERATOR YET
+        let res = suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &send, &[]);
+        assert!(res.is_err());
+
+        let msg = crate::msg::ExecuteMsg::UpdateOperators {
+            add: vec![USER.to_string()],
+            remove: vec![],
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), cw721_addr.clone(), &send, &[])
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::Purchase {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        //THE PROCEEDS GO TO THE SELLER THE OPERATOR NAMED
+        let res = suite
+            .query_balance(BIDDER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(1_000_001_000));
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+    }
+
+    #[test]
+    fn test_deposit_allowlist_minimum() {
+        let mut suite = Suite::init().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        let msg = crate::msg::ExecuteMsg::UpdateDepositDenoms {
+            add: vec![Coin::new(1_000, "utest")],
+            remove: vec![],
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //BELOW THE MINIMUM, THE FUNDS STAY WITH THE SENDER
+        let msg = crate::msg::ExecuteMsg::Deposit {};
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[Coin::new(500, "utest")],
+        );
+        assert!(res.is_err());
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "utest")],
+            )
+            .unwrap();
+
+        let res = suite
+            .query_balance(BUYER.to_string(), "utest".to_string())
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(999_999_000));
+
+        let value: DepositResponse = suite
+            .smart_query(
+                nft_marketplace_addr.to_string(),
+                crate::msg::QueryMsg::GetDeposits {
+                    address: BUYER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(value.deposits[0].amount, Coin::new(1_000, "utest"));
+    }
+
+    #[test]
+    fn test_purchase_from_cw20_balance_credits_seller() {
+        let mut suite = Suite::init().unwrap();
+        let cw20_addr = suite.instantiate_cw20().unwrap();
+        let cw721_addr = suite.instantiate_cw721().unwrap();
+        let nft_marketplace_addr = suite.instantiate_nft_marketplace().unwrap();
+
+        //MINT AN NFT TO THE SELLER AND LIST IT FOR 500 CW20
+        suite.mint_nft(&cw721_addr, "TNT").unwrap();
+        suite
+            .list_nft(
+                &cw721_addr,
+                &nft_marketplace_addr,
+                "TNT",
+                Currency::Cw20 {
+                    contract: cw20_addr.to_string(),
+                },
+                500,
+            )
+            .unwrap();
+
+        //SELLER KEEPS PROCEEDS IN THE MARKETPLACE
+        let msg = crate::msg::ExecuteMsg::SetCreditProceeds { enabled: true };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let msg = crate::msg::ExecuteMsg::PurchaseFromBalance {
+            cw721_contract: cw721_addr.to_string(),
+            token_id: "TNT".to_string(),
+        };
+
+        //BUYER HAS NO BALANCE YET
+        let res = suite.app.execute_contract(
+            Addr::unchecked(BUYER),
+            nft_marketplace_addr.clone(),
+            &msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        let hook = crate::msg::Cw20HookMsg::Deposit {};
+        let deposit = Cw20ExecuteMsg::Send {
+            contract: nft_marketplace_addr.to_string(),
+            amount: Uint128::new(800),
+            msg: to_binary(&hook).unwrap(),
+        };
+        suite
+            .app
+            .execute_contract(Addr::unchecked(BUYER), cw20_addr.clone(), &deposit, &[])
+            .unwrap();
+
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(BUYER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        //NFT GOES TO THE BUYER, NO CW20 LEAVES THE MARKETPLACE
+        let owner = suite.query_nft_owner(&cw721_addr, "TNT").unwrap();
+        assert_eq!(owner, BUYER.to_string());
+
+        let balance = suite
+            .query_cw20_balance(&cw20_addr, nft_marketplace_addr.as_str())
+            .unwrap();
+        assert_eq!(balance, Uint128::new(800));
+
+        let msg = QueryMsg::GetCw20Deposit {
+            address: BUYER.to_string(),
+        };
+        let value: Cw20DepositResponse = suite
+            .smart_query(nft_marketplace_addr.to_string(), msg)
+            .unwrap();
+        assert_eq!(value.deposits[0].amount, 300);
+
+        let msg = QueryMsg::GetCw20Deposit {
+            address: USER.to_string(),
+        };
+        let value: Cw20DepositResponse = suite
+            .smart_query(nft_marketplace_addr.to_string(), msg)
+            .unwrap();
+        assert_eq!(value.deposits[0].amount, 500);
+
+        //SELLER WITHDRAWS THE PROCEEDS
+        let msg = crate::msg::ExecuteMsg::WithdrawCw20 {
+            cw20_contract: cw20_addr.to_string(),
+            amount: 500,
+            recipient: None,
+        };
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                nft_marketplace_addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+
+        let balance = suite.query_cw20_balance(&cw20_addr, USER).unwrap();
+        assert_eq!(balance, Uint128::new(1_000_500));
+    }
 }
@@ -1,18 +1,50 @@
+use cosmwasm_std::{Binary, Coin, Timestamp, Uint128};
 use cw20::Cw20ReceiveMsg;
+use cw_utils::{Expiration, Scheduled};
 
 use cw721::Cw721ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::{Cw20Deposit, Cw721Deposit, Deposit};
+use crate::state::{
+    Bid, BidKey, BidTerms, CollectionOffer, Currency, Cw20Deposit, Cw721Deposit, Deposit, Offer,
+    TokenRef, TopUp, TraitCriterion,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    //defaults to the instantiator
+    pub admin: Option<String>,
+    pub fee_bps: u64,
+    //defaults to the admin
+    pub fee_recipient: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    UpdateConfig {
+        admin: Option<String>,
+        fee_bps: Option<u64>,
+        fee_recipient: Option<String>,
+    },
+    //only the admin, operators can deposit on behalf of other users
+    UpdateOperators {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    //only the admin, each coin is a denom and its minimum deposit
+    UpdateDepositDenoms {
+        add: Vec<Coin>,
+        remove: Vec<String>,
+    },
+    //only the collection's minter can set its fallback royalty
+    SetCollectionRoyalty {
+        cw721_contract: String,
+        payment_address: String,
+        share_bps: u64,
+    },
     Deposit {},
     Withdraw {
         amount: u128,
@@ -20,22 +52,235 @@ pub enum ExecuteMsg {
     },
     Receive(Cw20ReceiveMsg),
     ReceiveNft(Cw721ReceiveMsg),
+    //pays for an ask out of the buyer's deposit balance in the ask's currency
+    PurchaseFromBalance {
+        cw721_contract: String,
+        token_id: String,
+    },
+    //keep the sender's sale proceeds in their deposit balance instead of sending them out
+    SetCreditProceeds {
+        enabled: bool,
+    },
+    //only the depositor, recipient defaults to the sender
     WithdrawCw20 {
-        owner: String,
+        cw20_contract: String,
         amount: u128,
+        recipient: Option<String>,
     },
     WithdrawNft {
         cw721_contract: String,
         token_id: String,
     },
+    Purchase {
+        cw721_contract: String,
+        token_id: String,
+    },
+    //pays for a cw20 ask out of an allowance given to the marketplace
+    PurchaseWithAllowance {
+        cw721_contract: String,
+        token_id: String,
+        cw20_contract: String,
+    },
+    //only the seller, resets a dutch listing to a fixed price
+    UpdateAsk {
+        cw721_contract: String,
+        token_id: String,
+        price: u128,
+        currency: Currency,
+        expires_at: Option<Expiration>,
+    },
+    //removes the ask and returns the nft to the seller if it was deposited
+    CancelAsk {
+        cw721_contract: String,
+        token_id: String,
+    },
+    //lists a token the seller keeps, the marketplace must be approved to transfer it
+    ListWithApproval {
+        cw721_contract: String,
+        token_id: String,
+        currency: Currency,
+        price: u128,
+        expires_at: Option<Expiration>,
+        starts_at: Option<Scheduled>,
+    },
+    //anyone can remove an approval listing that was revoked or whose token changed hands
+    InvalidateAsk {
+        cw721_contract: String,
+        token_id: String,
+    },
+    Bid {
+        cw721_contract: String,
+        token_id: String,
+        expires_at: Option<Expiration>,
+    },
+    RetractBid {
+        cw721_contract: String,
+        token_id: String,
+    },
+    AcceptBid {
+        cw721_contract: String,
+        token_id: String,
+        bidder: String,
+        terms: BidTerms,
+    },
+    CreateCollectionOffer {
+        cw721_contract: String,
+        price: u128,
+        quantity: u64,
+        traits: Option<Vec<TraitCriterion>>,
+    },
+    CancelCollectionOffer {
+        cw721_contract: String,
+        offer_id: u64,
+    },
+    BidAuction {
+        cw721_contract: String,
+        token_id: String,
+    },
+    SettleAuction {
+        cw721_contract: String,
+        token_id: String,
+    },
+    //reads at most limit auctions after start_after, the response's last_* attributes resume it
+    SettleAuctions {
+        start_after: Option<TokenRef>,
+        limit: Option<u32>,
+    },
+    CommitSealedBid {
+        cw721_contract: String,
+        token_id: String,
+        commitment: Binary,
+    },
+    RevealSealedBid {
+        cw721_contract: String,
+        token_id: String,
+        amount: u128,
+        salt: String,
+    },
+    //pages through refunds, call again until the auction is gone
+    SettleSealedAuction {
+        cw721_contract: String,
+        token_id: String,
+        limit: Option<u32>,
+    },
+    //buys several asks priced in the one native denom sent, unspent funds are refunded
+    PurchaseMany {
+        items: Vec<TokenRef>,
+        //skip items that can't be bought instead of failing the whole cart
+        best_effort: Option<bool>,
+    },
+    //starts an empty bundle, items are sent in with Cw721HookMsg::AddToBundle
+    CreateBundle {
+        currency: Currency,
+        price: u128,
+    },
+    //no items can be added once the bundle is listed
+    ListBundle {
+        bundle_id: u64,
+    },
+    //returns every item to the seller
+    CancelBundle {
+        bundle_id: u64,
+    },
+    PurchaseBundle {
+        bundle_id: u64,
+    },
+    //offered items are sent in with Cw721HookMsg::OfferToSwap
+    CreateSwap {
+        counterparty: Option<String>,
+        wanted: Vec<TokenRef>,
+        top_up: Option<TopUp>,
+    },
+    //only the maker, no items can be offered once the swap is open
+    OpenSwap {
+        swap_id: u64,
+    },
+    //pays a native top-up, wanted nfts are sent with Cw721HookMsg::FillSwap
+    FillSwap {
+        swap_id: u64,
+    },
+    //only the taker, returns what they sent so far
+    RetractSwapFill {
+        swap_id: u64,
+    },
+    //only the maker, returns the offered items and anything the taker sent
+    CancelSwap {
+        swap_id: u64,
+    },
+    //returns expired asks' nfts and expired bids' funds to their owners
+    //reads at most limit of each after its cursor, the response's last_* attributes resume it
+    PruneExpired {
+        asks_start_after: Option<TokenRef>,
+        bids_start_after: Option<BidKey>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    GetCw20Deposit { address: String },
-    GetDeposits { address: String },
-    GetCw721Deposit { address: String, contract: String },
+    GetConfig {},
+    GetOperators {},
+    GetDepositDenoms {},
+    GetCreditProceeds {
+        address: String,
+    },
+    GetCollectionRoyalty {
+        cw721_contract: String,
+    },
+    GetCw20Deposit {
+        address: String,
+    },
+    GetDeposits {
+        address: String,
+    },
+    GetCw721Deposit {
+        address: String,
+        contract: String,
+    },
+    //start_after is a bidder
+    GetBidsForToken {
+        cw721_contract: String,
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetBidsByBidder {
+        bidder: String,
+        start_after: Option<TokenRef>,
+        limit: Option<u32>,
+    },
+    //start_after is an offer id
+    GetCollectionOffers {
+        cw721_contract: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetAuction {
+        cw721_contract: String,
+        token_id: String,
+    },
+    GetCurrentPrice {
+        cw721_contract: String,
+        token_id: String,
+    },
+    //all asks for a collection when no status is given, start_after is a token_id
+    GetAsks {
+        cw721_contract: String,
+        status: Option<AskStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetSealedAuction {
+        cw721_contract: String,
+        token_id: String,
+    },
+    GetBundle {
+        bundle_id: u64,
+    },
+    GetSwap {
+        swap_id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -45,25 +290,124 @@ pub enum MigrateMsg {}
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    Deposit {
+    //credits the sender with the amount sent
+    Deposit {},
+    //only allowlisted operators, credits owner instead of the sender
+    DepositFor {
         owner: String,
-        amount: u128,
     },
     Purchase {
         token_id: String,
         cw721_contract: String,
     },
+    Bid {
+        cw721_contract: String,
+        token_id: String,
+        expires_at: Option<Expiration>,
+    },
+    CreateCollectionOffer {
+        cw721_contract: String,
+        price: u128,
+        quantity: u64,
+        traits: Option<Vec<TraitCriterion>>,
+    },
+    BidAuction {
+        cw721_contract: String,
+        token_id: String,
+    },
+    CommitSealedBid {
+        cw721_contract: String,
+        token_id: String,
+        commitment: Binary,
+    },
+    PurchaseBundle {
+        bundle_id: u64,
+    },
+    FillSwap {
+        swap_id: u64,
+    },
+    PurchaseMany {
+        items: Vec<TokenRef>,
+        best_effort: Option<bool>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw721HookMsg {
+    //lists the sent token for its sender
     Deposit {
+        currency: Currency,
+        amount: u128,
+        expires_at: Option<Expiration>,
+        starts_at: Option<Scheduled>,
+    },
+    //only allowlisted operators, lists the sent token for owner
+    DepositFor {
         owner: String,
-        token_id: String,
-        cw20_contract: String,
+        currency: Currency,
         amount: u128,
+        expires_at: Option<Expiration>,
+        starts_at: Option<Scheduled>,
+    },
+    AcceptBid {
+        bidder: String,
+        terms: BidTerms,
+    },
+    //only the bundle's owner, before it is listed
+    AddToBundle {
+        bundle_id: u64,
+    },
+    //only the swap's maker, before it is open
+    OfferToSwap {
+        swap_id: u64,
+    },
+    FillSwap {
+        swap_id: u64,
+    },
+    FillCollectionOffer {
+        offer_id: u64,
+    },
+    CreateAuction {
+        currency: Currency,
+        reserve: u128,
+        min_increment: u128,
+        end_time: Timestamp,
+        extension_window: Option<u64>,
+    },
+    CreateDutchAuction {
+        currency: Currency,
+        start_price: u128,
+        end_price: u128,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        step_interval: Option<u64>,
     },
+    CreateSealedAuction {
+        currency: Currency,
+        reserve: u128,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+        non_reveal_penalty_bps: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OperatorsResponse {
+    pub operators: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CreditProceedsResponse {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DepositDenomsResponse {
+    pub denoms: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -83,3 +427,55 @@ pub struct DepositResponse {
 pub struct Cw721DepositResponse {
     pub deposits: Vec<Cw721Deposit>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BidsResponse {
+    pub bids: Vec<Bid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CollectionOffersResponse {
+    pub offers: Vec<CollectionOffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AskStatus {
+    //not started yet
+    Upcoming,
+    //started and not expired
+    Live,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AsksResponse {
+    pub asks: Vec<Offer>,
+    //the last token_id read, set when the page was full; filtered asks still count towards limit
+    pub next_start_after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CurrentPriceResponse {
+    pub currency: Currency,
+    pub price: u128,
+}
+
+//cw2981 query sent to the cw721 contract when a sale settles
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw2981QueryMsg {
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
@@ -1,8 +1,26 @@
-use cosmwasm_std::Coin;
+use std::fmt;
+
+use cosmwasm_std::{Binary, Coin, Empty, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Map;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::{Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub admin: String,
+    //protocol fee taken from every sale, in basis points
+    pub fee_bps: u64,
+    pub fee_recipient: String,
+}
+
+//fallback royalty for collections that don't implement cw2981
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionRoyalty {
+    pub payment_address: String,
+    pub share_bps: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Cw20Deposit {
@@ -26,15 +44,184 @@ pub struct Deposit {
     pub count: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Currency {
+    Native { denom: String },
+    Cw20 { contract: String },
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Currency::Native { denom } => write!(f, "native:{}", denom),
+            Currency::Cw20 { contract } => write!(f, "cw20:{}", contract),
+        }
+    }
+}
+
+//dutch auction schedule: the price falls from start_price to end_price between the two times
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceDecline {
+    pub start_price: u128,
+    pub end_price: u128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    //seconds between price drops, None = linear
+    pub step_interval: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Offer {
     pub owner: String,
     pub token_id: String,
     pub cw721_contract: String,
-    pub cw20_contract: String,
+    pub currency: Currency,
+    pub amount: u128,
+    pub decline: Option<PriceDecline>,
+    pub expires_at: Option<Expiration>,
+    //listed ahead of time, purchasable once triggered
+    pub starts_at: Option<Scheduled>,
+    //false when the seller keeps the nft and only approved the marketplace
+    pub custodial: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bid {
+    pub bidder: String,
+    pub cw721_contract: String,
+    pub token_id: String,
+    pub currency: Currency,
+    pub amount: u128,
+    pub expires_at: Option<Expiration>,
+}
+
+//what the seller saw when accepting, a bid swapped out from under them is rejected
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidTerms {
+    pub currency: Currency,
+    pub min_amount: u128,
+}
+
+//primary key of a bid, used to resume a crank over every bid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidKey {
+    pub cw721_contract: String,
+    pub token_id: String,
+    pub bidder: String,
+}
+
+//points at a single nft, wherever it's held
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenRef {
+    pub cw721_contract: String,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BundleItem {
+    pub cw721_contract: String,
+    pub token_id: String,
+}
+
+//several nfts, possibly from different collections, sold together for one price
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bundle {
+    pub id: u64,
+    pub owner: String,
+    pub items: Vec<BundleItem>,
+    pub currency: Currency,
+    pub amount: u128,
+    //items can only be added before the bundle is listed
+    pub listed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopUp {
+    pub currency: Currency,
     pub amount: u128,
 }
 
+//the maker escrows offered nfts and the taker completes it by sending every wanted nft and the top-up
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swap {
+    pub id: u64,
+    pub maker: String,
+    //only this address may fill, None = anyone
+    pub counterparty: Option<String>,
+    pub offered: Vec<TokenRef>,
+    pub wanted: Vec<TokenRef>,
+    pub top_up: Option<TopUp>,
+    //set by the first fill, cleared if the taker retracts
+    pub taker: Option<String>,
+    //wanted items the taker has already sent in
+    pub received: Vec<TokenRef>,
+    pub top_up_paid: bool,
+    //the offered items are fixed once the swap is open
+    pub open: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitCriterion {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionOffer {
+    pub id: u64,
+    pub buyer: String,
+    pub cw721_contract: String,
+    pub currency: Currency,
+    pub price: u128,
+    pub quantity: u64,
+    //empty = any token in the collection
+    pub traits: Vec<TraitCriterion>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub seller: String,
+    pub cw721_contract: String,
+    pub token_id: String,
+    pub currency: Currency,
+    pub reserve: u128,
+    pub min_increment: u128,
+    pub end_time: Timestamp,
+    //seconds; a bid landing this close to end_time pushes it back
+    pub extension_window: u64,
+    pub highest_bidder: Option<String>,
+    pub highest_bid: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SealedAuction {
+    pub seller: String,
+    pub cw721_contract: String,
+    pub token_id: String,
+    pub currency: Currency,
+    pub reserve: u128,
+    pub commit_end: Timestamp,
+    pub reveal_end: Timestamp,
+    //basis points of collateral kept from bidders who never reveal, paid to the seller
+    pub non_reveal_penalty_bps: u64,
+    pub highest_bidder: Option<String>,
+    pub highest_bid: u128,
+    //the nft and sale are done, the auction stays until every collateral is refunded
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SealedBid {
+    pub bidder: String,
+    //sha256 of "{bidder}:{cw721_contract}:{token_id}:{amount}:{salt}"
+    pub commitment: Binary,
+    pub collateral: u128,
+    pub revealed: Option<u128>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
 //key = owner addr, denom
 pub const DEPOSITS: Map<(&str, &str), Deposit> = Map::new("deposits");
 
@@ -44,5 +231,66 @@ pub const CW20_DEPOSITS: Map<(&str, &str), Cw20Deposit> = Map::new("cw20deposits
 //key = owner addr, contract_addr, token_id
 pub const CW721_DEPOSITS: Map<(&str, &str, &str), Cw721Deposit> = Map::new("cw721deposits");
 
+//key = operator addr, allowed to deposit on behalf of other users
+pub const OPERATORS: Map<&str, Empty> = Map::new("operators");
+
+//key = seller addr, sale proceeds are credited to their deposit balance instead of sent out
+pub const CREDIT_PROCEEDS: Map<&str, Empty> = Map::new("credit_proceeds");
+
+//key = denom, value = minimum deposit, deposits are open to any denom while this is empty
+pub const DEPOSIT_DENOMS: Map<&str, Uint128> = Map::new("deposit_denoms");
+
+//key = cw721 contract addr
+pub const COLLECTION_ROYALTIES: Map<&str, CollectionRoyalty> = Map::new("collection_royalties");
+
 //key = cw721 contract addr, token_id
 pub const ASKS: Map<(&str, &str), Offer> = Map::new("asks");
+
+//key = cw721 contract addr, offer id
+pub const COLLECTION_OFFERS: Map<(&str, u64), CollectionOffer> = Map::new("collection_offers");
+
+//key = cw721 contract addr, token_id
+pub const AUCTIONS: Map<(&str, &str), Auction> = Map::new("auctions");
+
+//key = cw721 contract addr, token_id
+pub const SEALED_AUCTIONS: Map<(&str, &str), SealedAuction> = Map::new("sealed_auctions");
+
+//key = cw721 contract addr, token_id, bidder addr
+pub const SEALED_BIDS: Map<(&str, &str, &str), SealedBid> = Map::new("sealed_bids");
+
+pub const COLLECTION_OFFER_COUNT: Item<u64> = Item::new("collection_offer_count");
+
+//key = bundle id
+pub const BUNDLES: Map<u64, Bundle> = Map::new("bundles");
+
+//key = cw721 contract addr, token_id, value = id of the bundle holding it
+pub const BUNDLE_ITEMS: Map<(&str, &str), u64> = Map::new("bundle_items");
+
+pub const BUNDLE_COUNT: Item<u64> = Item::new("bundle_count");
+
+//key = swap id
+pub const SWAPS: Map<u64, Swap> = Map::new("swaps");
+
+//key = cw721 contract addr, token_id, value = id of the swap holding it
+pub const SWAP_ITEMS: Map<(&str, &str), u64> = Map::new("swap_items");
+
+pub const SWAP_COUNT: Item<u64> = Item::new("swap_count");
+
+pub struct BidIndexes<'a> {
+    pub bidder: MultiIndex<'a, String, Bid, (String, String, String)>,
+}
+
+impl<'a> IndexList<Bid> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Bid>> + '_> {
+        let v: Vec<&dyn Index<Bid>> = vec![&self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+//key = cw721 contract addr, token_id, bidder addr
+pub fn bids<'a>() -> IndexedMap<'a, (&'a str, &'a str, &'a str), Bid, BidIndexes<'a>> {
+    let indexes = BidIndexes {
+        bidder: MultiIndex::new(|bid: &Bid| bid.bidder.clone(), "bids", "bids__bidder"),
+    };
+    IndexedMap::new("bids", indexes)
+}
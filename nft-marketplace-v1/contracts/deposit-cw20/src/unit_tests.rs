@@ -1,18 +1,25 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{from_binary, to_binary, DepsMut, Response, Uint128};
+    use cosmwasm_std::{
+        from_binary, to_binary, BankMsg, ContractResult, CosmosMsg, DepsMut, Response,
+        SystemResult, Uint128, WasmMsg, WasmQuery,
+    };
 
-    use cw20::Cw20ReceiveMsg;
-    use cw721::Cw721ReceiveMsg;
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw721::{Approval, Cw721ReceiveMsg, OwnerOfResponse};
+    use cw_utils::{Expiration, Scheduled};
 
-    use crate::contract::{execute, instantiate, query};
+    use crate::contract::{execute, instantiate, query, sealed_bid_commitment};
     use crate::error::ContractError;
     use crate::msg::{
-        Cw20DepositResponse, Cw20HookMsg, Cw721DepositResponse, Cw721HookMsg, DepositResponse,
-        ExecuteMsg, InstantiateMsg, QueryMsg,
+        AskStatus, AsksResponse, BidsResponse, CollectionOffersResponse, CreditProceedsResponse,
+        CurrentPriceResponse, Cw20DepositResponse, Cw20HookMsg, Cw2981QueryMsg,
+        Cw721DepositResponse, Cw721HookMsg, DepositDenomsResponse, DepositResponse, ExecuteMsg,
+        InstantiateMsg, OperatorsResponse, QueryMsg, RoyaltiesInfoResponse,
     };
+    use crate::state::{BidTerms, Config, Currency, SealedAuction, TokenRef, TopUp};
 
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
     use cosmwasm_std::Coin;
 
     const SENDER: &str = "sender_address";
@@ -20,47 +27,50 @@ mod tests {
     const DENOM: &str = "utest";
 
     fn proper_instantiate(deps: DepsMut) -> Result<Response, ContractError> {
-        let msg = InstantiateMsg {};
-        let info = mock_info(&SENDER, &[]);
+        let msg = InstantiateMsg {
+            admin: None,
+            fee_bps: 0,
+            fee_recipient: None,
+        };
+        let info = mock_info(SENDER, &[]);
         instantiate(deps, mock_env(), info, msg)
     }
 
     fn execute_cw20_deposit(deps: DepsMut) -> Result<Response, ContractError> {
         let cw20_msg = Cw20ReceiveMsg {
-            sender: "".to_string(),
-            amount: Uint128::new(0),
-            msg: to_binary(&Cw20HookMsg::Deposit {
-                owner: "right_guy".to_string(),
-                amount: 100u128,
-            })?,
+            sender: "right_guy".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&Cw20HookMsg::Deposit {})?,
         };
 
         let msg = ExecuteMsg::Receive(cw20_msg);
-        let info = mock_info(&"contract_addr", &[]);
+        let info = mock_info("contract_addr", &[]);
         execute(deps, mock_env(), info, msg)
     }
 
     fn execute_cw721_deposit(deps: DepsMut) -> Result<Response, ContractError> {
         let cw721_msg = Cw721ReceiveMsg {
-            sender: "".to_string(),
-            token_id: "".to_string(),
+            sender: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+            token_id: "TNT".to_string(),
             msg: to_binary(&Cw721HookMsg::Deposit {
-                owner: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
-                token_id: "TNT".to_string(),
-                cw20_contract: "cw20addr".to_string(),
+                currency: Currency::Cw20 {
+                    contract: "cw20addr".to_string(),
+                },
                 amount: 100,
+                expires_at: None,
+                starts_at: None,
             })?,
         };
 
         let msg = ExecuteMsg::ReceiveNft(cw721_msg);
-        let info = mock_info(&"contract_addr", &[]);
+        let info = mock_info("contract_addr", &[]);
         execute(deps, mock_env(), info, msg)
     }
 
     fn execute_deposit(deps: DepsMut) -> Result<Response, ContractError> {
         let msg = ExecuteMsg::Deposit {};
         let info = mock_info(
-            &SENDER,
+            SENDER,
             &[Coin {
                 amount: Uint128::new(AMOUNT),
                 denom: DENOM.to_string(),
@@ -118,7 +128,7 @@ mod tests {
             cw721_contract: "contract_addr".to_string(),
             token_id: "TNT".to_string(),
         };
-        let info = mock_info(&"juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
+        let info = mock_info("juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
         //println!("RES: {:?}", res);
 
@@ -155,7 +165,7 @@ mod tests {
             cw721_contract: "contract_addr".to_string(),
             token_id: "TNT".to_string(),
         };
-        let info = mock_info(&"juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
+        let info = mock_info("juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap(); */
         //println!("RES: {:?}", res);
 
@@ -170,8 +180,9 @@ mod tests {
         };
 
         let msg = ExecuteMsg::Receive(cw20_msg);
-        let info = mock_info(&"buyer_addr", &[]);
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("cw20addr", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
         //println!("RES: {:?}", res);
 
         let msg = QueryMsg::GetCw721Deposit {
@@ -187,121 +198,2423 @@ mod tests {
     }
 
     #[test]
-    fn test_deposit_and_query() {
+    fn test_purchase_with_wrong_cw20() {
         let mut deps = mock_dependencies();
-        let _res = execute_deposit(deps.as_mut()).unwrap();
-        let _res = execute_deposit(deps.as_mut()).unwrap();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_cw721_deposit(deps.as_mut()).unwrap();
 
-        let msg = QueryMsg::GetDeposits {
-            address: "wrong_guy".to_string(),
+        let cw20_msg = Cw20ReceiveMsg {
+            sender: "buyer_addr".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&Cw20HookMsg::Purchase {
+                token_id: "TNT".to_string(),
+                cw721_contract: "contract_addr".to_string(),
+            })
+            .unwrap(),
         };
-        let res = query(deps.as_ref(), mock_env(), msg);
+
+        let msg = ExecuteMsg::Receive(cw20_msg);
+        let info = mock_info("other_cw20addr", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
 
         match res {
-            Err(_) => {}
+            Err(ContractError::InvalidCoin {}) => {}
             _ => panic!("Should error here"),
         }
+    }
 
-        let msg = QueryMsg::GetDeposits {
-            address: SENDER.to_string(),
+    fn execute_native_cw721_deposit(deps: DepsMut) -> Result<Response, ContractError> {
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 100,
+                expires_at: None,
+                starts_at: None,
+            })?,
         };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
 
-        let res: DepositResponse = from_binary(&res).unwrap();
+        let msg = ExecuteMsg::ReceiveNft(cw721_msg);
+        let info = mock_info("contract_addr", &[]);
+        execute(deps, mock_env(), info, msg)
+    }
 
-        println!("RES: {:?}", res);
+    #[test]
+    fn test_update_and_cancel_ask() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::UpdateAsk {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            price: 200,
+            currency: Currency::Native {
+                denom: DENOM.to_string(),
+            },
+            expires_at: None,
+        };
+
+        //only the seller
+        let info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InvalidOwner {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let seller = mock_info("juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), seller.clone(), msg).unwrap();
+
+        //the old price no longer buys it
+        let purchase = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, purchase.clone());
+        match res {
+            Err(ContractError::InsufficientFunds {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::CancelAsk {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), seller.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&nft::contract::ExecuteMsg::TransferNft {
+                    recipient: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+                    token_id: "TNT".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let res = execute(deps.as_mut(), mock_env(), seller.clone(), msg);
+        match res {
+            Err(ContractError::NoAsk {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //withdrawing the nft takes the ask down with it
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+        let msg = ExecuteMsg::WithdrawNft {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), seller, msg).unwrap();
+
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, purchase);
+        match res {
+            Err(ContractError::NoAsk {}) => {}
+            _ => panic!("Should error here"),
+        }
     }
+
     #[test]
-    fn test_deposit_and_withdraw() {
+    fn test_expired_ask_and_bid_are_rejected_and_pruned() {
         let mut deps = mock_dependencies();
-        let _res = execute_deposit(deps.as_mut()).unwrap();
-        let _res = execute_deposit(deps.as_mut()).unwrap();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
 
-        let msg = QueryMsg::GetDeposits {
-            address: SENDER.to_string(),
+        let expires_at = Expiration::AtTime(mock_env().block.time.plus_seconds(60));
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 100,
+                expires_at: Some(expires_at),
+                starts_at: None,
+            })
+            .unwrap(),
         };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
-        let res: DepositResponse = from_binary(&res).unwrap();
-        assert_eq!(res.deposits[0].amount.amount, Uint128::new(200000));
+        let info = mock_info("contract_addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        )
+        .unwrap();
 
-        let msg = ExecuteMsg::Withdraw {
-            amount: 1,
-            denom: DENOM.to_string(),
+        let msg = ExecuteMsg::Bid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: Some(expires_at),
         };
-        let info = mock_info(&SENDER, &[]);
+        let info = mock_info("bidder_addr", &[Coin::new(80, DENOM)]);
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        //println!("RES: {:?}", res);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
 
-        let msg = QueryMsg::GetDeposits {
-            address: SENDER.to_string(),
+        let msg = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, msg);
+        match res {
+            Err(ContractError::Expired {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::AcceptBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            bidder: "bidder_addr".to_string(),
+            terms: BidTerms {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                min_amount: 80,
+            },
+        };
+        let info = mock_info("seller_addr", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, msg);
+        match res {
+            Err(ContractError::Expired {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //anyone can prune, the nft goes back to the seller and the bid is refunded
+        let msg = ExecuteMsg::PruneExpired {
+            asks_start_after: None,
+            bids_start_after: None,
+            limit: None,
+        };
+        let info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&nft::contract::ExecuteMsg::TransferNft {
+                    recipient: "seller_addr".to_string(),
+                    token_id: "TNT".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "bidder_addr".to_string(),
+                amount: vec![Coin::new(80, DENOM)],
+            })
+        );
+
+        let msg = QueryMsg::GetBidsForToken {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            start_after: None,
+            limit: None,
         };
         let res = query(deps.as_ref(), mock_env(), msg).unwrap();
-        let res: DepositResponse = from_binary(&res).unwrap();
-        assert_eq!(res.deposits[0].amount.amount, Uint128::new(199999));
+        let res: BidsResponse = from_binary(&res).unwrap();
+        assert!(res.bids.is_empty());
     }
 
     #[test]
-    fn test_cw20_deposit_and_query() {
+    fn test_prune_expired_pages_by_read() {
         let mut deps = mock_dependencies();
         let _res = proper_instantiate(deps.as_mut()).unwrap();
-        let _res = execute_cw20_deposit(deps.as_mut()).unwrap();
 
-        let query_msg = QueryMsg::GetCw20Deposit {
-            address: "wrong_guy".to_string(),
+        for (token_id, seconds) in [("TNT1", 600), ("TNT2", 60)] {
+            let cw721_msg = Cw721ReceiveMsg {
+                sender: "seller_addr".to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&Cw721HookMsg::Deposit {
+                    currency: Currency::Native {
+                        denom: DENOM.to_string(),
+                    },
+                    amount: 100,
+                    expires_at: Some(Expiration::AtTime(
+                        mock_env().block.time.plus_seconds(seconds),
+                    )),
+                    starts_at: None,
+                })
+                .unwrap(),
+            };
+            let info = mock_info("contract_addr", &[]);
+            let _res = execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::ReceiveNft(cw721_msg),
+            )
+            .unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+
+        //the first page only reads the live ask
+        let msg = ExecuteMsg::PruneExpired {
+            asks_start_after: None,
+            bids_start_after: None,
+            limit: Some(1),
         };
-        let res = query(deps.as_ref(), mock_env(), query_msg);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        let last = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "last_ask_token_id")
+            .unwrap();
+        assert_eq!(last.value, "TNT1");
+
+        let msg = ExecuteMsg::PruneExpired {
+            asks_start_after: Some(TokenRef {
+                cw721_contract: "contract_addr".to_string(),
+                token_id: "TNT1".to_string(),
+            }),
+            bids_start_after: None,
+            limit: Some(1),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&nft::contract::ExecuteMsg::TransferNft {
+                    recipient: "seller_addr".to_string(),
+                    token_id: "TNT2".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_scheduled_ask_upcoming_then_live() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        //a drop listed now that opens in a minute
+        let starts_at = Scheduled::AtTime(mock_env().block.time.plus_seconds(60));
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "DROP".to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 100,
+                expires_at: None,
+                starts_at: Some(starts_at),
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: Some(AskStatus::Upcoming),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 1);
+        assert_eq!(res.asks[0].token_id, "DROP");
+
+        let msg = QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: Some(AskStatus::Live),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 1);
+        assert_eq!(res.asks[0].token_id, "TNT");
+
+        //a filtered page still only reads limit asks and says where to resume
+        let msg = QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: Some(AskStatus::Live),
+            start_after: None,
+            limit: Some(1),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert!(res.asks.is_empty());
+        assert_eq!(res.next_start_after, Some("DROP".to_string()));
 
+        let msg = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "DROP".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
         match res {
-            Err(_) => {}
+            Err(ContractError::AskNotStarted {}) => {}
             _ => panic!("Should error here"),
         }
 
-        let query_msg = QueryMsg::GetCw20Deposit {
-            address: "right_guy".to_string(),
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+
+        let live = QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: Some(AskStatus::Live),
+            start_after: None,
+            limit: None,
         };
-        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let res = query(deps.as_ref(), env.clone(), live).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 2);
 
-        let _res: Cw20DepositResponse = from_binary(&res).unwrap();
-        //println!("AMOUNT IN DEPOSIT CONTRACT: {:?}", res);
+        //one page at a time, resuming after the last token_id
+        let page = |start_after: Option<String>| QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: Some(AskStatus::Live),
+            start_after,
+            limit: Some(1),
+        };
+        let res = query(deps.as_ref(), env.clone(), page(None)).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 1);
+        assert_eq!(res.asks[0].token_id, "DROP");
+        let res = query(deps.as_ref(), env.clone(), page(Some("DROP".to_string()))).unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 1);
+        assert_eq!(res.asks[0].token_id, "TNT");
+
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
     }
 
     #[test]
-    fn test_cw20_deposit_and_withdraw() {
+    fn test_list_with_approval_checks_owner_and_approval() {
         let mut deps = mock_dependencies();
         let _res = proper_instantiate(deps.as_mut()).unwrap();
-        let _res = execute_cw20_deposit(deps.as_mut()).unwrap();
 
-        let query_msg = QueryMsg::GetCw20Deposit {
-            address: "right_guy".to_string(),
+        //the seller still holds the token and approved the marketplace
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => {
+                let res = OwnerOfResponse {
+                    owner: "seller_addr".to_string(),
+                    approvals: vec![Approval {
+                        spender: MOCK_CONTRACT_ADDR.to_string(),
+                        expires: cw721::Expiration::Never {},
+                    }],
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => panic!("Unexpected query"),
+        });
+
+        let msg = ExecuteMsg::ListWithApproval {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            currency: Currency::Native {
+                denom: DENOM.to_string(),
+            },
+            price: 100,
+            expires_at: None,
+            starts_at: None,
         };
-        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let res: Cw20DepositResponse = from_binary(&res).unwrap();
-        println!("AMOUNT IN CONTRACT AFTER 1ST DEPOSIT: {:?}", res);
+        let info = mock_info("someone_else", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::NotApproved {}) => {}
+            _ => panic!("Should error here"),
+        }
 
-        let msg = ExecuteMsg::WithdrawCw20 {
-            owner: "wrong_guy".to_string(),
-            amount: 100u128,
+        let info = mock_info("seller_addr", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        //still valid, so nobody can invalidate it
+        let msg = ExecuteMsg::InvalidateAsk {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
         };
-        let info = mock_info("contract_addr", &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
         match res {
-            Err(ContractError::NoCw20ToWithdraw {}) => {}
-            _ => panic!("should error here"),
+            Err(ContractError::AskStillValid {}) => {}
+            _ => panic!("Should error here"),
         }
 
-        let msg = ExecuteMsg::WithdrawCw20 {
-            owner: "right_guy".to_string(),
-            amount: 100u128,
+        //nothing to send back on cancel, the seller kept the nft
+        let msg = ExecuteMsg::CancelAsk {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
         };
-        let info = mock_info("contract_addr", &[]);
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn test_list_with_operator_approval() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        //no approval on the token itself, but the marketplace is an operator for the seller
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+                nft::helpers::QueryMsg::OwnerOf { .. } => {
+                    let res = OwnerOfResponse {
+                        owner: "seller_addr".to_string(),
+                        approvals: vec![],
+                    };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                nft::helpers::QueryMsg::AllOperators { owner, .. } => {
+                    assert_eq!(owner, "seller_addr");
+                    let res = cw721::OperatorsResponse {
+                        operators: vec![Approval {
+                            spender: MOCK_CONTRACT_ADDR.to_string(),
+                            expires: cw721::Expiration::Never {},
+                        }],
+                    };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => panic!("Unexpected query"),
+            },
+            _ => panic!("Unexpected query"),
+        });
+
+        let msg = ExecuteMsg::ListWithApproval {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            currency: Currency::Native {
+                denom: DENOM.to_string(),
+            },
+            price: 100,
+            expires_at: None,
+            starts_at: None,
+        };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("seller_addr", &[]),
+            msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::InvalidateAsk {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::AskStillValid {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_escrowing_an_approval_listed_token_keeps_its_deposit() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => {
+                let res = OwnerOfResponse {
+                    owner: "seller_addr".to_string(),
+                    approvals: vec![Approval {
+                        spender: MOCK_CONTRACT_ADDR.to_string(),
+                        expires: cw721::Expiration::Never {},
+                    }],
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => panic!("Unexpected query"),
+        });
+
+        let seller = mock_info("seller_addr", &[]);
+        let msg = ExecuteMsg::ListWithApproval {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            currency: Currency::Native {
+                denom: DENOM.to_string(),
+            },
+            price: 100,
+            expires_at: Some(Expiration::AtTime(mock_env().block.time.plus_seconds(60))),
+            starts_at: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), seller.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateBundle {
+            currency: Currency::Native {
+                denom: DENOM.to_string(),
+            },
+            price: 100,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), seller.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::AddToBundle { bundle_id: 1 }).unwrap(),
+        });
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_addr", &[]),
+            msg,
+        )
+        .unwrap();
+
+        //the approval ask goes away once the token is in escrow
+        let msg = QueryMsg::GetAsks {
+            cw721_contract: "contract_addr".to_string(),
+            status: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: AsksResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.asks.is_empty());
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::PruneExpired {
+            asks_start_after: None,
+            bids_start_after: None,
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let msg = QueryMsg::GetCw721Deposit {
+            address: "seller_addr".to_string(),
+            contract: "contract_addr".to_string(),
+        };
+        let res: Cw721DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.deposits.len(), 1);
+
+        //and the bundle still hands the token back
+        let msg = ExecuteMsg::CancelBundle { bundle_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), seller, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_purchase_with_allowance_pulls_price_first() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_cw721_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::PurchaseWithAllowance {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            cw20_contract: "othercw20".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::PurchaseWithAllowance {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            cw20_contract: "cw20addr".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer_addr", &[Coin::new(100, DENOM)]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "cw20addr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "buyer_addr".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_bundle_lifecycle_errors() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::CreateBundle {
+            currency: Currency::Cw20 {
+                contract: "cw20addr".to_string(),
+            },
+            price: 100,
+        };
+        let info = mock_info("seller_addr", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        //an empty bundle can't be listed
+        let msg = ExecuteMsg::ListBundle { bundle_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+        match res {
+            Err(ContractError::EmptyBundle {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //only the bundle's owner can add to it
+        let add_item = |sender: &str, token_id: &str| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: sender.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&Cw721HookMsg::AddToBundle { bundle_id: 1 }).unwrap(),
+            })
+        };
+        let nft_info = mock_info("contract_addr", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            nft_info.clone(),
+            add_item("other_addr", "TNT"),
+        );
+        match res {
+            Err(ContractError::InvalidOwner {}) => {}
+            _ => panic!("Should error here"),
+        }
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            nft_info.clone(),
+            add_item("seller_addr", "TNT"),
+        )
+        .unwrap();
+
+        let purchase = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "buyer_addr".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&Cw20HookMsg::PurchaseBundle { bundle_id: 1 }).unwrap(),
+        });
+        let cw20_info = mock_info("cw20addr", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            cw20_info.clone(),
+            purchase.clone(),
+        );
+        match res {
+            Err(ContractError::BundleNotListed {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        //listed bundles are closed to new items
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            nft_info,
+            add_item("seller_addr", "TNT2"),
+        );
+        match res {
+            Err(ContractError::BundleListed {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::WithdrawNft {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("seller_addr", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::ItemInBundle {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //nor can a bid on one item break up the bundle
+        let msg = ExecuteMsg::Bid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: None,
+        };
+        let bidder = mock_info("bidder_addr", &[Coin::new(1, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), bidder, msg).unwrap();
+        let msg = ExecuteMsg::AcceptBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            bidder: "bidder_addr".to_string(),
+            terms: BidTerms {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                min_amount: 1,
+            },
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("seller_addr", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::ItemInBundle {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("othercw20", &[]),
+            purchase.clone(),
+        );
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            cw20_info.clone(),
+            purchase.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+
+        let res = execute(deps.as_mut(), mock_env(), cw20_info, purchase);
+        match res {
+            Err(ContractError::NoBundle {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_swap_fill_errors_and_settlement() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let maker = mock_info("maker_addr", &[]);
+        let msg = ExecuteMsg::CreateSwap {
+            counterparty: Some("taker_addr".to_string()),
+            wanted: vec![],
+            top_up: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), maker.clone(), msg);
+        match res {
+            Err(ContractError::EmptySwap {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let shield = TokenRef {
+            cw721_contract: "other_nft".to_string(),
+            token_id: "SHIELD".to_string(),
+        };
+        let msg = ExecuteMsg::CreateSwap {
+            counterparty: Some("taker_addr".to_string()),
+            wanted: vec![shield.clone(), shield],
+            top_up: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), maker.clone(), msg);
+        match res {
+            Err(ContractError::DuplicateSwapItem {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::CreateSwap {
+            counterparty: Some("taker_addr".to_string()),
+            wanted: vec![TokenRef {
+                cw721_contract: "other_nft".to_string(),
+                token_id: "SHIELD".to_string(),
+            }],
+            top_up: Some(TopUp {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 50,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), maker.clone(), msg).unwrap();
+
+        let nft_msg = |sender: &str, token_id: &str, hook: &Cw721HookMsg| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: sender.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(hook).unwrap(),
+            })
+        };
+        let offer = Cw721HookMsg::OfferToSwap { swap_id: 1 };
+        let fill = Cw721HookMsg::FillSwap { swap_id: 1 };
+
+        //nothing offered yet
+        let msg = ExecuteMsg::OpenSwap { swap_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), maker.clone(), msg.clone());
+        match res {
+            Err(ContractError::EmptySwap {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_addr", &[]),
+            nft_msg("maker_addr", "SWORD", &offer),
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_addr", &[]),
+            nft_msg("maker_addr", "SWORD", &offer),
+        );
+        match res {
+            Err(ContractError::DuplicateSwapItem {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_nft", &[]),
+            nft_msg("taker_addr", "SHIELD", &fill),
+        );
+        match res {
+            Err(ContractError::SwapNotOpen {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let _res = execute(deps.as_mut(), mock_env(), maker, msg).unwrap();
+
+        //only the named counterparty can fill
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_nft", &[]),
+            nft_msg("someone_else", "SHIELD", &fill),
+        );
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_nft", &[]),
+            nft_msg("taker_addr", "HELMET", &fill),
+        );
+        match res {
+            Err(ContractError::UnwantedSwapItem {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::WithdrawNft {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "SWORD".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("maker_addr", &[]), msg);
+        match res {
+            Err(ContractError::ItemInSwap {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other_nft", &[]),
+            nft_msg("taker_addr", "SHIELD", &fill),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 0);
+
+        //a bid can't pull a filled item back out of the swap
+        let msg = ExecuteMsg::Bid {
+            cw721_contract: "other_nft".to_string(),
+            token_id: "SHIELD".to_string(),
+            expires_at: None,
+        };
+        let info = mock_info("bidder_addr", &[Coin::new(1, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::AcceptBid {
+            cw721_contract: "other_nft".to_string(),
+            token_id: "SHIELD".to_string(),
+            bidder: "bidder_addr".to_string(),
+            terms: BidTerms {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                min_amount: 1,
+            },
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("taker_addr", &[]), msg);
+        match res {
+            Err(ContractError::ItemInSwap {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::FillSwap { swap_id: 1 };
+        let info = mock_info("taker_addr", &[Coin::new(50, "uother")]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //both nfts and the top-up move in one response
+        let info = mock_info("taker_addr", &[Coin::new(50, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker_addr".to_string(),
+                amount: vec![Coin::new(50, DENOM)],
+            })
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetSwap { swap_id: 1 });
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_purchase_many_all_or_nothing_and_best_effort() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+            token_id: "TNT2".to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 200,
+                expires_at: None,
+                starts_at: None,
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        )
+        .unwrap();
+
+        let item = |token_id: &str| TokenRef {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(150, DENOM)]);
+
+        let msg = ExecuteMsg::PurchaseMany {
+            items: vec![],
+            best_effort: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::NothingPurchased {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::PurchaseMany {
+            items: vec![item("MISSING"), item("TNT")],
+            best_effort: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::NoAsk {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::PurchaseMany {
+            items: vec![item("TNT2")],
+            best_effort: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer_addr", &[Coin::new(150, "uother")]),
+            msg,
+        );
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //the missing ask is skipped and 150 only covers TNT
+        let msg = ExecuteMsg::PurchaseMany {
+            items: vec![item("MISSING"), item("TNT"), item("TNT2")],
+            best_effort: Some(true),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer_addr".to_string(),
+                amount: vec![Coin::new(50, DENOM)],
+            })
+        );
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetAsks {
+                cw721_contract: "contract_addr".to_string(),
+                status: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let res: AsksResponse = from_binary(&res).unwrap();
+        assert_eq!(res.asks.len(), 1);
+        assert_eq!(res.asks[0].token_id, "TNT2".to_string());
+    }
+
+    #[test]
+    fn test_deposits_credit_the_envelope_sender() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        //the amount comes from the envelope, not the payload
+        let cw20_msg = Cw20ReceiveMsg {
+            sender: "depositor".to_string(),
+            amount: Uint128::new(40),
+            msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        };
+        let info = mock_info("cw20addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Receive(cw20_msg),
+        )
+        .unwrap();
+
+        let query_msg = QueryMsg::GetCw20Deposit {
+            address: "depositor".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let res: Cw20DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[0].amount, 40);
+
+        let deposit_for = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "router".to_string(),
+            amount: Uint128::new(60),
+            msg: to_binary(&Cw20HookMsg::DepositFor {
+                owner: "depositor".to_string(),
+            })
+            .unwrap(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deposit_for.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //only the admin manages operators
+        let msg = ExecuteMsg::UpdateOperators {
+            add: vec!["router".to_string()],
+            remove: vec![],
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("router", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Should error here"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(SENDER, &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetOperators {}).unwrap();
+        let res: OperatorsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.operators, vec!["router".to_string()]);
+
+        let _res = execute(deps.as_mut(), mock_env(), info, deposit_for).unwrap();
+        let query_msg = QueryMsg::GetCw20Deposit {
+            address: "depositor".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let res: Cw20DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[0].amount, 100);
+
+        //a listing belongs to whoever sent the token
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::DepositFor {
+                owner: "someone_else".to_string(),
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                amount: 100,
+                expires_at: None,
+                starts_at: None,
+            })
+            .unwrap(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_addr", &[]),
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        );
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_deposit_credits_every_coin_and_checks_allowlist() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let info = mock_info(SENDER, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {});
+        match res {
+            Err(ContractError::NoFunds {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info(SENDER, &[Coin::new(10, DENOM), Coin::new(0, "uother")]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {});
+        match res {
+            Err(ContractError::ZeroAmount {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info(SENDER, &[Coin::new(10, DENOM), Coin::new(20, "uother")]);
+        let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits.len(), 2);
+
+        //once the admin lists a denom only listed denoms are accepted
+        let msg = ExecuteMsg::UpdateDepositDenoms {
+            add: vec![Coin::new(10, DENOM)],
+            remove: vec![],
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(SENDER, &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetDepositDenoms {}).unwrap();
+        let res: DepositDenomsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.denoms, vec![Coin::new(10, DENOM)]);
+
+        let info = mock_info(SENDER, &[Coin::new(20, "uother")]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {});
+        match res {
+            Err(ContractError::DenomNotAllowed { denom }) => assert_eq!(denom, "uother"),
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info(SENDER, &[Coin::new(9, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {});
+        match res {
+            Err(ContractError::DepositBelowMinimum { minimum, .. }) => {
+                assert_eq!(minimum, Uint128::new(10))
+            }
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info(SENDER, &[Coin::new(10, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[1].amount, Coin::new(20, DENOM));
+    }
+
+    #[test]
+    fn test_unknown_hook_payloads_are_rejected() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "depositor".to_string(),
+            amount: Uint128::new(10),
+            msg: to_binary("not a hook").unwrap(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("cw20addr", &[]), msg);
+        match res {
+            Err(ContractError::UnknownHookMsg {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary("not a hook").unwrap(),
+        });
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("contract_addr", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::UnknownHookMsg {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_withdraw_deposit_errors() {
+        let mut deps = mock_dependencies();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::Withdraw {
+            amount: 1,
+            denom: "uother".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(SENDER, &[]), msg);
+        match res {
+            Err(ContractError::UnknownDenom { denom }) => assert_eq!(denom, "uother"),
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::Withdraw {
+            amount: AMOUNT + 1,
+            denom: DENOM.to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(SENDER, &[]), msg);
+        match res {
+            Err(ContractError::InsufficientBalance {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_deposit_overflow_is_an_error() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let info = mock_info(SENDER, &[Coin::new(u128::MAX, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+        let info = mock_info(SENDER, &[Coin::new(1, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {});
+        match res {
+            Err(ContractError::Overflow {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let deposit = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "depositor".to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+            })
+        };
+        let info = mock_info("cw20addr", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), deposit(u128::MAX)).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, deposit(1));
+        match res {
+            Err(ContractError::Overflow {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //price times quantity can't be escrowed
+        let msg = ExecuteMsg::CreateCollectionOffer {
+            cw721_contract: "contract_addr".to_string(),
+            price: u128::MAX,
+            quantity: 2,
+            traits: None,
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(1, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::Overflow {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_purchase_from_balance_and_credit_proceeds() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+
+        let seller = "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h";
+        let msg = ExecuteMsg::SetCreditProceeds { enabled: true };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(seller, &[]), msg).unwrap();
+
+        let msg = QueryMsg::GetCreditProceeds {
+            address: seller.to_string(),
+        };
+        let res: CreditProceedsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.enabled);
+
+        let msg = ExecuteMsg::PurchaseFromBalance {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+
+        //no balance
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer_addr", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::InsufficientBalance {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //only the nft moves, the seller's share stays in the contract
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(SENDER, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res: DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.deposits[0].amount, Coin::new(AMOUNT - 100, DENOM));
+
+        let msg = QueryMsg::GetDeposits {
+            address: seller.to_string(),
+        };
+        let res: DepositResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.deposits[0].amount, Coin::new(100, DENOM));
+
+        //the seller can withdraw the proceeds like any deposit
+        let msg = ExecuteMsg::Withdraw {
+            amount: 100,
+            denom: DENOM.to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(seller, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: seller.to_string(),
+                amount: vec![Coin::new(100, DENOM)],
+            })
+        );
+
+        let msg = ExecuteMsg::SetCreditProceeds { enabled: false };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(seller, &[]), msg).unwrap();
+        let msg = QueryMsg::GetCreditProceeds {
+            address: seller.to_string(),
+        };
+        let res: CreditProceedsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.enabled);
+    }
+
+    #[test]
+    fn test_accept_bid_checks_terms() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::Bid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: None,
+        };
+        let info = mock_info("bidder_addr", &[Coin::new(80, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let accept = |currency: &str, min_amount: u128| ExecuteMsg::AcceptBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            bidder: "bidder_addr".to_string(),
+            terms: BidTerms {
+                currency: Currency::Native {
+                    denom: currency.to_string(),
+                },
+                min_amount,
+            },
+        };
+        let seller = mock_info("juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h", &[]);
+
+        //the bid was lowered or moved to another currency after the seller saw it
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            seller.clone(),
+            accept(DENOM, 100),
+        );
+        match res {
+            Err(ContractError::BidTermsChanged {}) => {}
+            _ => panic!("Should error here"),
+        }
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            seller.clone(),
+            accept("uother", 80),
+        );
+        match res {
+            Err(ContractError::BidTermsChanged {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), seller, accept(DENOM, 80)).unwrap();
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+                amount: vec![Coin::new(80, DENOM)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_native_purchase_refunds_overpayment() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+
+        //wrong denom
+        let info = mock_info("buyer_addr", &[Coin::new(100, "uother")]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InvalidCoin {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        //not enough
+        let info = mock_info("buyer_addr", &[Coin::new(99, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InsufficientFunds {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info("buyer_addr", &[Coin::new(150, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+                amount: vec![Coin::new(100, DENOM)],
+            })
+        );
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer_addr".to_string(),
+                amount: vec![Coin::new(50, DENOM)],
+            })
+        );
+
+        //the ask is gone
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::NoAsk {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_bid_query_and_retract() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::Bid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            expires_at: None,
+        };
+        let info = mock_info("bidder_addr", &[Coin::new(100, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        //only one bid per bidder and token
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::InvalidBid {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = QueryMsg::GetBidsForToken {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.bids.len(), 1);
+        assert_eq!(res.bids[0].amount, 100);
+
+        let msg = QueryMsg::GetBidsByBidder {
+            bidder: "bidder_addr".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.bids[0].token_id, "TNT");
+
+        let msg = ExecuteMsg::RetractBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("bidder_addr", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "bidder_addr".to_string(),
+                amount: vec![Coin::new(100, DENOM)],
+            })
+        );
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::NoBidsForTokenID {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = QueryMsg::GetBidsByBidder {
+            bidder: "bidder_addr".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: BidsResponse = from_binary(&res).unwrap();
+        assert!(res.bids.is_empty());
+    }
+
+    #[test]
+    fn test_bid_queries_page() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        for (bidder, token_id) in [
+            ("bidder_a", "TNT"),
+            ("bidder_b", "TNT"),
+            ("bidder_a", "TNT2"),
+        ] {
+            let msg = ExecuteMsg::Bid {
+                cw721_contract: "contract_addr".to_string(),
+                token_id: token_id.to_string(),
+                expires_at: None,
+            };
+            let info = mock_info(bidder, &[Coin::new(100, DENOM)]);
+            let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let msg = QueryMsg::GetBidsForToken {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            start_after: None,
+            limit: Some(1),
+        };
+        let res: BidsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.bids.len(), 1);
+        assert_eq!(res.bids[0].bidder, "bidder_a");
+
+        let msg = QueryMsg::GetBidsForToken {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            start_after: Some("bidder_a".to_string()),
+            limit: Some(1),
+        };
+        let res: BidsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.bids.len(), 1);
+        assert_eq!(res.bids[0].bidder, "bidder_b");
+
+        let msg = QueryMsg::GetBidsByBidder {
+            bidder: "bidder_a".to_string(),
+            start_after: None,
+            limit: Some(1),
+        };
+        let res: BidsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.bids.len(), 1);
+        assert_eq!(res.bids[0].token_id, "TNT");
+
+        let msg = QueryMsg::GetBidsByBidder {
+            bidder: "bidder_a".to_string(),
+            start_after: Some(TokenRef {
+                cw721_contract: "contract_addr".to_string(),
+                token_id: "TNT".to_string(),
+            }),
+            limit: None,
+        };
+        let res: BidsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.bids.len(), 1);
+        assert_eq!(res.bids[0].token_id, "TNT2");
+    }
+
+    #[test]
+    fn test_collection_offer_escrow_and_cancel() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        //escrow must match price * quantity
+        let msg = ExecuteMsg::CreateCollectionOffer {
+            cw721_contract: "contract_addr".to_string(),
+            price: 100,
+            quantity: 3,
+            traits: None,
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(200, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InvalidEscrowAmount {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info("buyer_addr", &[Coin::new(300, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::GetCollectionOffers {
+            cw721_contract: "contract_addr".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: CollectionOffersResponse = from_binary(&res).unwrap();
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].quantity, 3);
+
+        //offers page by id
+        let msg = ExecuteMsg::CreateCollectionOffer {
+            cw721_contract: "contract_addr".to_string(),
+            price: 50,
+            quantity: 1,
+            traits: None,
+        };
+        let info = mock_info("other_buyer", &[Coin::new(50, DENOM)]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = QueryMsg::GetCollectionOffers {
+            cw721_contract: "contract_addr".to_string(),
+            start_after: Some(res.offers[0].id),
+            limit: Some(1),
+        };
+        let page = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: CollectionOffersResponse = from_binary(&page).unwrap();
+        assert_eq!(page.offers.len(), 1);
+        assert_eq!(page.offers[0].price, 50);
+
+        let msg = ExecuteMsg::CancelCollectionOffer {
+            cw721_contract: "contract_addr".to_string(),
+            offer_id: res.offers[0].id,
+        };
+        let info = mock_info("someone_else", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::InvalidOwner {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let info = mock_info("buyer_addr", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer_addr".to_string(),
+                amount: vec![Coin::new(300, DENOM)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_auction_needs_positive_min_increment() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let env = mock_env();
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                reserve: 100,
+                min_increment: 0,
+                end_time: env.block.time.plus_seconds(60),
+                extension_window: None,
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ReceiveNft(cw721_msg));
+        match res {
+            Err(ContractError::InvalidMinIncrement {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_settle_auctions_pages_by_read() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let env = mock_env();
+        for (token_id, seconds) in [("TNT1", 600), ("TNT2", 60)] {
+            let cw721_msg = Cw721ReceiveMsg {
+                sender: "seller_addr".to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&Cw721HookMsg::CreateAuction {
+                    currency: Currency::Native {
+                        denom: DENOM.to_string(),
+                    },
+                    reserve: 100,
+                    min_increment: 10,
+                    end_time: env.block.time.plus_seconds(seconds),
+                    extension_window: None,
+                })
+                .unwrap(),
+            };
+            let info = mock_info("contract_addr", &[]);
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                ExecuteMsg::ReceiveNft(cw721_msg),
+            )
+            .unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+
+        //the first page only reads the live auction
+        let msg = ExecuteMsg::SettleAuctions {
+            start_after: None,
+            limit: Some(1),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        let last = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "last_token_id")
+            .unwrap();
+        assert_eq!(last.value, "TNT1");
+
+        let msg = ExecuteMsg::SettleAuctions {
+            start_after: Some(TokenRef {
+                cw721_contract: "contract_addr".to_string(),
+                token_id: "TNT1".to_string(),
+            }),
+            limit: Some(1),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&nft::contract::ExecuteMsg::TransferNft {
+                    recipient: "seller_addr".to_string(),
+                    token_id: "TNT2".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_auction_without_bids_returns_nft() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let env = mock_env();
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                reserve: 100,
+                min_increment: 10,
+                end_time: env.block.time.plus_seconds(60),
+                extension_window: None,
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::SettleAuction {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&nft::contract::ExecuteMsg::TransferNft {
+                    recipient: "seller_addr".to_string(),
+                    token_id: "TNT".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let msg = QueryMsg::GetAuction {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    #[test]
+    fn test_sealed_bid_commit_and_reveal_phases() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+
+        let env = mock_env();
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateSealedAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                reserve: 100,
+                commit_end: env.block.time.plus_seconds(60),
+                reveal_end: env.block.time.plus_seconds(120),
+                non_reveal_penalty_bps: None,
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::CommitSealedBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            commitment: sealed_bid_commitment(SENDER, "contract_addr", "TNT", 150, "salt"),
+        };
+        let info = mock_info(SENDER, &[Coin::new(200, DENOM)]);
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+        //anyone can copy the commitment, but it only opens for the original bidder
+        let copycat = mock_info("copycat_addr", &[Coin::new(200, DENOM)]);
+        let _res = execute(deps.as_mut(), env.clone(), copycat.clone(), msg.clone()).unwrap();
+
+        //can't reveal while bids are still being committed
+        let reveal = ExecuteMsg::RevealSealedBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            amount: 150,
+            salt: "salt".to_string(),
+        };
+        let res = execute(deps.as_mut(), env, info.clone(), reveal.clone());
+        match res {
+            Err(ContractError::NotRevealPhase {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+        match res {
+            Err(ContractError::NotCommitPhase {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let wrong_salt = ExecuteMsg::RevealSealedBid {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            amount: 150,
+            salt: "other".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), wrong_salt);
+        match res {
+            Err(ContractError::InvalidReveal {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let res = execute(deps.as_mut(), env.clone(), copycat, reveal.clone());
+        match res {
+            Err(ContractError::InvalidReveal {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let _res = execute(deps.as_mut(), env, info, reveal).unwrap();
+
+        let msg = QueryMsg::GetSealedAuction {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let auction: SealedAuction = from_binary(&res).unwrap();
+        assert_eq!(auction.highest_bidder, Some(SENDER.to_string()));
+        assert_eq!(auction.highest_bid, 150);
+
+        //one page moves the nft and refunds one of the two bidders
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(120);
+        let msg = ExecuteMsg::SettleSealedAuction {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            limit: Some(1),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+
+        //the token can't go back up for auction until every bidder is refunded
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: SENDER.to_string(),
+            token_id: "TNT".to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateSealedAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                reserve: 100,
+                commit_end: env.block.time.plus_seconds(60),
+                reveal_end: env.block.time.plus_seconds(120),
+                non_reveal_penalty_bps: None,
+            })
+            .unwrap(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("contract_addr", &[]),
+            ExecuteMsg::ReceiveNft(cw721_msg),
+        );
+        match res {
+            Err(ContractError::SealedAuctionSettling {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = ExecuteMsg::SettleSealedAuction {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_update_config_and_fee_split() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            admin: None,
+            fee_bps: Some(250),
+            fee_recipient: Some("fee_addr".to_string()),
+        };
+
+        //only the admin
+        let info = mock_info("anyone", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let bad_fee = ExecuteMsg::UpdateConfig {
+            admin: None,
+            fee_bps: Some(10_001),
+            fee_recipient: None,
+        };
+        let info = mock_info(SENDER, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), bad_fee);
+        match res {
+            Err(ContractError::InvalidFee {}) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: Config = from_binary(&res).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                admin: SENDER.to_string(),
+                fee_bps: 250,
+                fee_recipient: "fee_addr".to_string(),
+            }
+        );
+
+        //2.5% of the 100 sale goes to the fee recipient
+        let msg = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee_addr".to_string(),
+                amount: vec![Coin::new(2, DENOM)],
+            })
+        );
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+                amount: vec![Coin::new(98, DENOM)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_cw2981_royalty_paid_before_seller() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_native_cw721_deposit(deps.as_mut()).unwrap();
+
+        //the cw721 contract implements cw2981 with a 10% royalty
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => match from_binary(msg) {
+                Ok(Cw2981QueryMsg::RoyaltyInfo { sale_price, .. }) => {
+                    let res = RoyaltiesInfoResponse {
+                        address: "creator_addr".to_string(),
+                        royalty_amount: sale_price.multiply_ratio(1u128, 10u128),
+                    };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                Err(_) => panic!("Unexpected query"),
+            },
+            _ => panic!("Unexpected query"),
+        });
+
+        let msg = ExecuteMsg::Purchase {
+            cw721_contract: "contract_addr".to_string(),
+            token_id: "TNT".to_string(),
+        };
+        let info = mock_info("buyer_addr", &[Coin::new(100, DENOM)]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator_addr".to_string(),
+                amount: vec![Coin::new(10, DENOM)],
+            })
+        );
+        assert_eq!(
+            res.messages[2].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "juno1pqn6edrdmr28ekdjv5j2u9uvh6m32tl306kh5h".to_string(),
+                amount: vec![Coin::new(90, DENOM)],
+            })
+        );
+    }
+
+    fn execute_dutch_deposit(
+        deps: DepsMut,
+        token_id: &str,
+        step_interval: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let env = mock_env();
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: token_id.to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateDutchAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                start_price: 1000,
+                end_price: 200,
+                start_time: env.block.time,
+                end_time: env.block.time.plus_seconds(100),
+                step_interval,
+            })?,
+        };
+
+        let msg = ExecuteMsg::ReceiveNft(cw721_msg);
+        let info = mock_info("contract_addr", &[]);
+        execute(deps, env, info, msg)
+    }
+
+    #[test]
+    fn test_dutch_auction_current_price() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_dutch_deposit(deps.as_mut(), "LINEAR", None).unwrap();
+        let _res = execute_dutch_deposit(deps.as_mut(), "STEPPED", Some(30)).unwrap();
+
+        let price_at = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, token_id: &str, secs: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(secs);
+            let msg = QueryMsg::GetCurrentPrice {
+                cw721_contract: "contract_addr".to_string(),
+                token_id: token_id.to_string(),
+            };
+            let res: CurrentPriceResponse =
+                from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+            res.price
+        };
+
+        assert_eq!(price_at(&deps, "LINEAR", 0), 1000);
+        assert_eq!(price_at(&deps, "LINEAR", 50), 600);
+        assert_eq!(price_at(&deps, "LINEAR", 500), 200);
+        assert_eq!(price_at(&deps, "STEPPED", 29), 1000);
+        assert_eq!(price_at(&deps, "STEPPED", 50), 760);
+        assert_eq!(price_at(&deps, "STEPPED", 99), 280);
+
+        //end price below start price is the only valid direction
+        let env = mock_env();
+        let cw721_msg = Cw721ReceiveMsg {
+            sender: "seller_addr".to_string(),
+            token_id: "BAD".to_string(),
+            msg: to_binary(&Cw721HookMsg::CreateDutchAuction {
+                currency: Currency::Native {
+                    denom: DENOM.to_string(),
+                },
+                start_price: 200,
+                end_price: 1000,
+                start_time: env.block.time,
+                end_time: env.block.time.plus_seconds(100),
+                step_interval: None,
+            })
+            .unwrap(),
+        };
+        let info = mock_info("contract_addr", &[]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ReceiveNft(cw721_msg));
+        match res {
+            Err(ContractError::InvalidPriceDecline {}) => {}
+            _ => panic!("Should error here"),
+        }
+    }
+
+    #[test]
+    fn test_deposit_and_query() {
+        let mut deps = mock_dependencies();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+
+        let msg = QueryMsg::GetDeposits {
+            address: "wrong_guy".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg);
+
+        match res {
+            Err(_) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let res: DepositResponse = from_binary(&res).unwrap();
+
+        println!("RES: {:?}", res);
+    }
+    #[test]
+    fn test_deposit_and_withdraw() {
+        let mut deps = mock_dependencies();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+        let _res = execute_deposit(deps.as_mut()).unwrap();
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[0].amount.amount, Uint128::new(200000));
+
+        let msg = ExecuteMsg::Withdraw {
+            amount: 1,
+            denom: DENOM.to_string(),
+        };
+        let info = mock_info(SENDER, &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        //println!("RES: {:?}", res);
+
+        let msg = QueryMsg::GetDeposits {
+            address: SENDER.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[0].amount.amount, Uint128::new(199999));
+    }
+
+    #[test]
+    fn test_cw20_deposit_and_query() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_cw20_deposit(deps.as_mut()).unwrap();
+
+        let query_msg = QueryMsg::GetCw20Deposit {
+            address: "wrong_guy".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg);
+
+        match res {
+            Err(_) => {}
+            _ => panic!("Should error here"),
+        }
+
+        let query_msg = QueryMsg::GetCw20Deposit {
+            address: "right_guy".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+
+        let _res: Cw20DepositResponse = from_binary(&res).unwrap();
+        //println!("AMOUNT IN DEPOSIT CONTRACT: {:?}", res);
+    }
+
+    #[test]
+    fn test_cw20_deposit_and_withdraw() {
+        let mut deps = mock_dependencies();
+        let _res = proper_instantiate(deps.as_mut()).unwrap();
+        let _res = execute_cw20_deposit(deps.as_mut()).unwrap();
+
+        let query_msg = QueryMsg::GetCw20Deposit {
+            address: "right_guy".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let res: Cw20DepositResponse = from_binary(&res).unwrap();
+        println!("AMOUNT IN CONTRACT AFTER 1ST DEPOSIT: {:?}", res);
+
+        let msg = ExecuteMsg::WithdrawCw20 {
+            cw20_contract: "contract_addr".to_string(),
+            amount: 100u128,
+            recipient: None,
+        };
+        let info = mock_info("wrong_guy", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::NoCw20ToWithdraw {}) => {}
+            _ => panic!("should error here"),
+        }
+
+        let msg = ExecuteMsg::WithdrawCw20 {
+            cw20_contract: "contract_addr".to_string(),
+            amount: 0u128,
+            recipient: None,
+        };
+        let info = mock_info("right_guy", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::ZeroAmount {}) => {}
+            _ => panic!("should error here"),
+        }
+
+        let msg = ExecuteMsg::WithdrawCw20 {
+            cw20_contract: "contract_addr".to_string(),
+            amount: 101u128,
+            recipient: None,
+        };
+        let info = mock_info("right_guy", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::InsufficientBalance {}) => {}
+            _ => panic!("should error here"),
+        }
+
+        let msg = ExecuteMsg::WithdrawCw20 {
+            cw20_contract: "contract_addr".to_string(),
+            amount: 100u128,
+            recipient: Some("other_guy".to_string()),
+        };
+        let info = mock_info("right_guy", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract_addr".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "other_guy".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
 
         let query_msg = QueryMsg::GetCw20Deposit {
             address: "right_guy".to_string(),
         };
         let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
         let res: Cw20DepositResponse = from_binary(&res).unwrap();
+        assert_eq!(res.deposits[0].amount, 0);
         println!("AMOUNT IN CONTRACT AFTER WITHDRAWAL: {:?}", res);
     }
 }
@@ -7,7 +7,10 @@ use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
     NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
-use nft::contract::{ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg};
+use nft::contract::{
+    CheckRoyaltiesResponse, ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg,
+    RoyaltiesInfoResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -36,4 +39,6 @@ fn main() {
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(RoyaltiesInfoResponse), &out_dir);
+    export_schema(&schema_for!(CheckRoyaltiesResponse), &out_dir);
 }
\ No newline at end of file
@@ -1,9 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Empty;
+use cosmwasm_std::{Deps, Empty, StdError, StdResult, Uint128};
 use cw2::set_contract_version;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse};
+pub use cw721_base::{MintMsg, MinterResponse};
+use cw_storage_plus::Item;
+
+pub use crate::error::ContractError;
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cw721-metadata-onchain";
@@ -28,20 +31,228 @@ pub struct Metadata {
     pub background_color: Option<String>,
     pub animation_url: Option<String>,
     pub youtube_url: Option<String>,
+    /// cw2981 royalty for this token, a whole percentage of the sale price.
+    /// Falls back to the collection default when unset.
+    pub royalty_percentage: Option<u64>,
+    pub royalty_payment_address: Option<String>,
 }
 
 pub type Extension = Option<Metadata>;
 
 pub type Cw721MetadataContract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty>;
 pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
-pub type QueryMsg = cw721_base::QueryMsg;
+
+/// Collection wide royalty used by tokens that don't set their own
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DefaultRoyalty {
+    pub royalty_percentage: u64,
+    pub royalty_payment_address: String,
+}
+
+pub const DEFAULT_ROYALTY: Item<DefaultRoyalty> = Item::new("default_royalty");
+
+/// cw721-base instantiate plus an optional collection royalty
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub minter: String,
+    pub royalty_percentage: Option<u64>,
+    pub royalty_payment_address: Option<String>,
+}
+
+/// cw721-base queries plus the cw2981 royalty queries
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Royalty owed on a sale of this token at this price
+    /// Return type: RoyaltiesInfoResponse
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// Whether this contract implements cw2981 royalties
+    /// Return type: CheckRoyaltiesResponse
+    CheckRoyalties {},
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    AllOperators {
+        owner: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    NumTokens {},
+    ContractInfo {},
+    NftInfo {
+        token_id: String,
+    },
+    AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Minter {},
+}
+
+/// Fails for the royalty queries, which cw721-base doesn't know about
+impl TryFrom<QueryMsg> for cw721_base::QueryMsg {
+    type Error = StdError;
+
+    fn try_from(msg: QueryMsg) -> StdResult<cw721_base::QueryMsg> {
+        Ok(match msg {
+            QueryMsg::OwnerOf {
+                token_id,
+                include_expired,
+            } => cw721_base::QueryMsg::OwnerOf {
+                token_id,
+                include_expired,
+            },
+            QueryMsg::Approval {
+                token_id,
+                spender,
+                include_expired,
+            } => cw721_base::QueryMsg::Approval {
+                token_id,
+                spender,
+                include_expired,
+            },
+            QueryMsg::Approvals {
+                token_id,
+                include_expired,
+            } => cw721_base::QueryMsg::Approvals {
+                token_id,
+                include_expired,
+            },
+            QueryMsg::AllOperators {
+                owner,
+                include_expired,
+                start_after,
+                limit,
+            } => cw721_base::QueryMsg::AllOperators {
+                owner,
+                include_expired,
+                start_after,
+                limit,
+            },
+            QueryMsg::NumTokens {} => cw721_base::QueryMsg::NumTokens {},
+            QueryMsg::ContractInfo {} => cw721_base::QueryMsg::ContractInfo {},
+            QueryMsg::NftInfo { token_id } => cw721_base::QueryMsg::NftInfo { token_id },
+            QueryMsg::AllNftInfo {
+                token_id,
+                include_expired,
+            } => cw721_base::QueryMsg::AllNftInfo {
+                token_id,
+                include_expired,
+            },
+            QueryMsg::Tokens {
+                owner,
+                start_after,
+                limit,
+            } => cw721_base::QueryMsg::Tokens {
+                owner,
+                start_after,
+                limit,
+            },
+            QueryMsg::AllTokens { start_after, limit } => {
+                cw721_base::QueryMsg::AllTokens { start_after, limit }
+            }
+            QueryMsg::Minter {} => cw721_base::QueryMsg::Minter {},
+            QueryMsg::RoyaltyInfo { .. } | QueryMsg::CheckRoyalties {} => {
+                return Err(StdError::generic_err(
+                    "royalty queries are not part of cw721-base",
+                ))
+            }
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+/// Royalty for a token, from its own metadata or else the collection default
+pub fn query_royalties_info(
+    deps: Deps,
+    token_id: String,
+    sale_price: Uint128,
+) -> StdResult<RoyaltiesInfoResponse> {
+    let token = Cw721MetadataContract::default()
+        .tokens
+        .load(deps.storage, &token_id)?;
+    let default = DEFAULT_ROYALTY.may_load(deps.storage)?;
+
+    let metadata = token.extension.unwrap_or_default();
+    let percentage = metadata
+        .royalty_percentage
+        .or_else(|| default.as_ref().map(|d| d.royalty_percentage));
+    let address = metadata
+        .royalty_payment_address
+        .or_else(|| default.map(|d| d.royalty_payment_address));
+
+    match (percentage, address) {
+        (Some(percentage), Some(address)) => Ok(RoyaltiesInfoResponse {
+            address,
+            royalty_amount: sale_price.multiply_ratio(percentage, 100u128),
+        }),
+        _ => Ok(RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+        }),
+    }
+}
+
+/// A royalty needs both a percentage and an address, or neither
+fn validate_royalty(
+    deps: Deps,
+    percentage: Option<u64>,
+    address: &Option<String>,
+) -> Result<(), ContractError> {
+    match (percentage, address) {
+        (Some(percentage), Some(address)) => {
+            if percentage > 100 {
+                return Err(ContractError::InvalidRoyaltyPercentage {});
+            }
+            deps.api.addr_validate(address)?;
+        }
+        (None, None) => {}
+        _ => return Err(ContractError::IncompleteRoyalty {}),
+    }
+    Ok(())
+}
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{to_binary, Binary, DepsMut, Env, MessageInfo, Response};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
@@ -51,10 +262,30 @@ pub mod entry {
         info: MessageInfo,
         msg: InstantiateMsg,
     ) -> Result<Response, ContractError> {
-        let res = Cw721MetadataContract::default().instantiate(deps.branch(), env, info, msg)?;
+        validate_royalty(
+            deps.as_ref(),
+            msg.royalty_percentage,
+            &msg.royalty_payment_address,
+        )?;
+        if let (Some(royalty_percentage), Some(royalty_payment_address)) =
+            (msg.royalty_percentage, msg.royalty_payment_address)
+        {
+            let royalty = DefaultRoyalty {
+                royalty_percentage,
+                royalty_payment_address,
+            };
+            DEFAULT_ROYALTY.save(deps.storage, &royalty)?;
+        }
+
+        let base_msg = cw721_base::InstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            minter: msg.minter,
+        };
+        let res =
+            Cw721MetadataContract::default().instantiate(deps.branch(), env, info, base_msg)?;
         // Explicitly set contract name and version, otherwise set to cw721-base info
-        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)
-            .map_err(ContractError::Std)?;
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
         Ok(res)
     }
 
@@ -65,12 +296,31 @@ pub mod entry {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        Cw721MetadataContract::default().execute(deps, env, info, msg)
+        if let ExecuteMsg::Mint(mint_msg) = &msg {
+            if let Some(metadata) = &mint_msg.extension {
+                validate_royalty(
+                    deps.as_ref(),
+                    metadata.royalty_percentage,
+                    &metadata.royalty_payment_address,
+                )?;
+            }
+        }
+
+        Ok(Cw721MetadataContract::default().execute(deps, env, info, msg)?)
     }
 
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-        Cw721MetadataContract::default().query(deps, env, msg)
+        match msg {
+            QueryMsg::RoyaltyInfo {
+                token_id,
+                sale_price,
+            } => to_binary(&query_royalties_info(deps, token_id, sale_price)?),
+            QueryMsg::CheckRoyalties {} => to_binary(&CheckRoyaltiesResponse {
+                royalty_payments: true,
+            }),
+            _ => Cw721MetadataContract::default().query(deps, env, msg.try_into()?),
+        }
     }
 }
 
@@ -89,7 +339,7 @@ mod tests {
         let contract = Cw721MetadataContract::default();
 
         let info = mock_info(CREATOR, &[]);
-        let init_msg = InstantiateMsg {
+        let init_msg = cw721_base::InstantiateMsg {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
@@ -118,4 +368,91 @@ mod tests {
         assert_eq!(res.token_uri, mint_msg.token_uri);
         assert_eq!(res.extension, mint_msg.extension);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn royalty_info_uses_token_then_collection_default() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            royalty_percentage: Some(5),
+            royalty_payment_address: Some(CREATOR.to_string()),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let mint = |token_id: &str, extension: Extension| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension,
+            })
+        };
+
+        //over 100% is rejected on mint
+        let exec_msg = mint(
+            "Voyager",
+            Some(Metadata {
+                royalty_percentage: Some(101),
+                royalty_payment_address: Some("artist".to_string()),
+                ..Metadata::default()
+            }),
+        );
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage {});
+
+        //a percentage without an address is as incomplete here as on instantiate
+        let exec_msg = mint(
+            "Voyager",
+            Some(Metadata {
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+        );
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::IncompleteRoyalty {});
+
+        let exec_msg = mint(
+            "Enterprise",
+            Some(Metadata {
+                royalty_percentage: Some(10),
+                royalty_payment_address: Some("artist".to_string()),
+                ..Metadata::default()
+            }),
+        );
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let exec_msg = mint("Defiant", None);
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+
+        let res =
+            query_royalties_info(deps.as_ref(), "Enterprise".into(), Uint128::new(1_000)).unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "artist".to_string(),
+                royalty_amount: Uint128::new(100),
+            }
+        );
+
+        let res =
+            query_royalties_info(deps.as_ref(), "Defiant".into(), Uint128::new(1_000)).unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: CREATOR.to_string(),
+                royalty_amount: Uint128::new(50),
+            }
+        );
+
+        let res = entry::query(deps.as_ref(), mock_env(), QueryMsg::CheckRoyalties {}).unwrap();
+        let res: CheckRoyaltiesResponse = cosmwasm_std::from_binary(&res).unwrap();
+        assert!(res.royalty_payments);
+
+        //converting to a base query is fallible rather than a panic
+        let res = cw721_base::QueryMsg::try_from(QueryMsg::CheckRoyalties {});
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw721_base::ContractError),
+
+    #[error("Royalty percentage must be between 0 and 100")]
+    InvalidRoyaltyPercentage {},
+
+    #[error("Royalty needs both a percentage and a payment address")]
+    IncompleteRoyalty {},
+}
@@ -7,10 +7,10 @@ use cosmwasm_std::{
 
 //use crate::msg::{ExecuteMsg, GetCountResponse, QueryMsg};
 
-pub use cw721::{OwnerOfResponse, TokensResponse};
-pub use cw721_base::QueryMsg;
+pub use cw721::{NftInfoResponse, OperatorsResponse, OwnerOfResponse, TokensResponse};
+pub use cw721_base::{MinterResponse, QueryMsg};
 
-use crate::contract::ExecuteMsg;
+use crate::contract::{ExecuteMsg, Extension};
 
 /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -32,17 +32,22 @@ impl NftContract {
         .into())
     }
 
-    /// Get Owner of an NFT
-    pub fn get_owner<Q, T, CQ>(&self, querier: &Q, token_id:String) -> StdResult<OwnerOfResponse>
+    /// Get Owner of an NFT along with its unexpired approvals
+    pub fn get_owner<CQ>(&self, querier: &QuerierWrapper<CQ>, token_id: String) -> StdResult<OwnerOfResponse>
     where
-        Q:Querier,
-        T: Into<String>,
         CQ: CustomQuery,
     {
-        let msg = QueryMsg::OwnerOf { token_id:token_id, include_expired:None };
-        let query = WasmQuery::Smart { contract_addr: self.addr().into(), msg: to_binary(&msg)? }.into();
-        let res: OwnerOfResponse = QuerierWrapper::<CQ>::new(querier).query(&query)?;
-        Ok(res)
+        let msg = QueryMsg::OwnerOf { token_id, include_expired: None };
+        querier.query_wasm_smart(self.addr(), &msg)
+    }
+
+    /// Get the unexpired operators an owner approved for all of their tokens, first page only
+    pub fn operators<CQ>(&self, querier: &QuerierWrapper<CQ>, owner: String) -> StdResult<OperatorsResponse>
+    where
+        CQ: CustomQuery,
+    {
+        let msg = QueryMsg::AllOperators { owner, include_expired: None, start_after: None, limit: Some(100) };
+        querier.query_wasm_smart(self.addr(), &msg)
     }
 
     /// Get All Tokens
@@ -57,5 +62,23 @@ impl NftContract {
         let res: TokensResponse = QuerierWrapper::<CQ>::new(querier).query(&query)?;
         Ok(res)
     }
-    
+
+    /// Get Nft Info (token_uri and on-chain metadata) of an NFT
+    pub fn nft_info<CQ>(&self, querier: &QuerierWrapper<CQ>, token_id: String) -> StdResult<NftInfoResponse<Extension>>
+    where
+        CQ: CustomQuery,
+    {
+        let msg = QueryMsg::NftInfo { token_id };
+        querier.query_wasm_smart(self.addr(), &msg)
+    }
+
+    /// Get the Minter of the collection
+    pub fn minter<CQ>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<MinterResponse>
+    where
+        CQ: CustomQuery,
+    {
+        let msg = QueryMsg::Minter {};
+        querier.query_wasm_smart(self.addr(), &msg)
+    }
+
 }
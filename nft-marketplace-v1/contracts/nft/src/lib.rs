@@ -1,2 +1,3 @@
 pub mod contract;
-pub mod helpers;
\ No newline at end of file
+pub mod error;
+pub mod helpers;